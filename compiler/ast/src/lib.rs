@@ -10,22 +10,61 @@ pub struct ParsedFile<'input> {
 pub enum AstDefinitions<'input> {
     Function(AstFunction<'input>),
     Extern(ExternFnDeclaration<'input>),
-    Import(ImportDirective<'input>)
+    Import(ImportDirective<'input>),
+    Const(AstConst<'input>),
+    Struct(AstStruct<'input>),
+    Impl(AstImpl<'input>),
+    Trait(AstTrait<'input>),
+    TypeAlias(AstTypeAlias<'input>)
 }
 
 #[derive(Debug, Clone)]
 pub enum AstExpr<'input> {
     Identifier(&'input str), 
-    Integer(i64), 
-    Float(f64), 
+    // the value without its sign, whether a minus was written in front of it and the suffix
+    Integer(u128, bool, Option<&'input str>), 
+    Float(f64, Option<&'input str>), 
     Bool(bool),
     String(&'input str),
     Block(Vec<AstExpr<'input>>), 
     Binary(BinaryExpression<'input>), 
     Call(CallExpression<'input>), 
+    // `alias::name`, a constant of an imported module
+    Path(&'input str, &'input str),
     Return(Option<Box<AstExpr<'input>>>), 
     VarDef(VariableDefinition<'input>),
     IfExpr(IfExpression<'input>),
+    Cast(CastExpression<'input>),
+    Closure(ClosureExpression<'input>),
+    StructLiteral(StructLiteral<'input>),
+    FieldAccess(FieldAccess<'input>),
+    MethodCall(MethodCallExpression<'input>),
+    Tuple(Vec<AstExpr<'input>>),
+    TupleIndex(TupleIndex<'input>),
+    Destructure(DestructureDefinition<'input>),
+    Array(Vec<AstExpr<'input>>),
+    Index(IndexExpression<'input>),
+    For(ForExpression<'input>),
+    Try(Box<AstExpr<'input>>),
+    Defer(Box<AstExpr<'input>>),
+}
+
+pub type AstTypedName<'input> = (&'input str, ExprTy<'input>);
+// a type parameter with its trait bounds
+pub type AstGenericParam<'input> = (&'input str, Vec<&'input str>);
+pub type AstParam<'input> = (AstPattern<'input>, ExprTy<'input>);
+pub type AstFieldValue<'input> = (&'input str, AstExpr<'input>);
+pub type AstFieldPattern<'input> = (&'input str, AstPattern<'input>);
+
+#[derive(Debug, Clone)]
+pub enum AstPattern<'input> {
+    Binding(&'input str),
+    Wildcard,
+    Tuple(Vec<AstPattern<'input>>),
+    Struct {
+        name: &'input str,
+        fields: Vec<AstFieldPattern<'input>>
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -35,6 +74,17 @@ pub enum ExprTy<'input> {
         elem_ty: Box<ExprTy<'input>>,
         size: usize,  
     },
+    Function {
+        params: Vec<ExprTy<'input>>,
+        ret: Option<Box<ExprTy<'input>>>
+    },
+    Dyn(&'input str),
+    Tuple(Vec<ExprTy<'input>>),
+    Slice(Box<ExprTy<'input>>),
+    Applied {
+        name: &'input str,
+        args: Vec<ExprTy<'input>>
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -59,7 +109,8 @@ pub struct IfExpression<'input> {
 
 #[derive(Debug, Clone)]
 pub struct ImportDirective<'input> {
-    pub import_name: &'input str,
+    pub path: Vec<&'input str>,
+    pub alias: Option<&'input str>,
     pub import_hash: &'input str,
     pub target_found: &'input str
 }
@@ -71,6 +122,13 @@ pub struct VariableDefinition<'input> {
     pub content: Box<AstExpr<'input>>
 }
 
+#[derive(Debug, Clone)]
+pub struct DestructureDefinition<'input> {
+    pub pattern: AstPattern<'input>,
+    pub ty: Option<ExprTy<'input>>,
+    pub content: Box<AstExpr<'input>>
+}
+
 #[derive(Debug, Clone)]
 pub struct CallExpression<'input> {
     pub alias: Option<&'input str>,
@@ -78,6 +136,56 @@ pub struct CallExpression<'input> {
     pub args: Vec<AstExpr<'input>>
 }
 
+#[derive(Debug, Clone)]
+pub struct ClosureExpression<'input> {
+    pub args: Vec<AstTypedName<'input>>,
+    pub body: Box<AstExpr<'input>>
+}
+
+#[derive(Debug, Clone)]
+pub struct StructLiteral<'input> {
+    pub name: &'input str,
+    pub fields: Vec<AstFieldValue<'input>>
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldAccess<'input> {
+    pub expr: Box<AstExpr<'input>>,
+    pub field: &'input str
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexExpression<'input> {
+    pub expr: Box<AstExpr<'input>>,
+    pub index: Box<AstExpr<'input>>
+}
+
+#[derive(Debug, Clone)]
+pub struct ForExpression<'input> {
+    pub pattern: AstPattern<'input>,
+    pub iterable: Box<AstExpr<'input>>,
+    pub body: Box<AstExpr<'input>>
+}
+
+#[derive(Debug, Clone)]
+pub struct TupleIndex<'input> {
+    pub expr: Box<AstExpr<'input>>,
+    pub index: usize
+}
+
+#[derive(Debug, Clone)]
+pub struct MethodCallExpression<'input> {
+    pub receiver: Box<AstExpr<'input>>,
+    pub method: &'input str,
+    pub args: Vec<AstExpr<'input>>
+}
+
+#[derive(Debug, Clone)]
+pub struct CastExpression<'input> {
+    pub expr: Box<AstExpr<'input>>,
+    pub ty: ExprTy<'input>
+}
+
 #[derive(Debug, Clone)]
 pub struct BinaryExpression<'input> {
     pub lhs: Box<AstExpr<'input>>,
@@ -85,11 +193,57 @@ pub struct BinaryExpression<'input> {
     pub operator: Token<'input>
 }
 
+#[derive(Debug, Clone)]
+pub struct AstConst<'input> {
+    pub name: &'input str,
+    pub ty: ExprTy<'input>,
+    pub visibility: Token<'input>,
+    pub value: Box<AstExpr<'input>>
+}
+
 #[derive(Debug, Clone)]
 pub struct AstFunction<'input> {
     pub name: String,
-    pub args: Vec<(&'input str, ExprTy<'input>)>,
+    pub generics: Vec<AstGenericParam<'input>>,
+    pub args: Vec<AstParam<'input>>,
     pub return_type: Option<ExprTy<'input>>,
     pub visibility: Token<'input>,
     pub body: Box<AstExpr<'input>>
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone)]
+pub struct AstStruct<'input> {
+    pub name: &'input str,
+    pub fields: Vec<AstTypedName<'input>>,
+    pub visibility: Token<'input>
+}
+
+#[derive(Debug, Clone)]
+pub struct AstTypeAlias<'input> {
+    pub name: &'input str,
+    pub ty: ExprTy<'input>,
+    // `newtype` instead of `type`
+    pub distinct: bool,
+    pub visibility: Token<'input>
+}
+
+#[derive(Debug, Clone)]
+pub struct AstImpl<'input> {
+    pub target: &'input str,
+    pub trait_name: Option<&'input str>,
+    pub methods: Vec<AstFunction<'input>>
+}
+
+#[derive(Debug, Clone)]
+pub struct AstTraitMethod<'input> {
+    pub name: &'input str,
+    pub args: Vec<AstParam<'input>>,
+    pub return_type: Option<ExprTy<'input>>
+}
+
+#[derive(Debug, Clone)]
+pub struct AstTrait<'input> {
+    pub name: &'input str,
+    pub methods: Vec<AstTraitMethod<'input>>,
+    pub visibility: Token<'input>
+}
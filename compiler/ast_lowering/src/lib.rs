@@ -1,7 +1,6 @@
-use core::panic;
-
-use ast::{AstDefinitions, AstExpr, ExprTy, ParsedFile};
-use hir::{HirBinOps, HirExpr, HirExprKind, HirFile, HirId, HirModuleItem, HirTyHint, HirVisibility};
+use ast::{AstDefinitions, AstExpr, AstFunction, AstPattern, AstTrait, ExprTy, ParsedFile};
+use hir::{HirBinOps, HirExpr, HirExprKind, HirFile, HirGeneric, HirId, HirImport, HirModuleItem, HirPattern, HirTraitMethod, HirTyHint, HirVariant, HirVisibility};
+use middle::GlobalCtx;
 use tokens::Token;
 
 fn remap_visibility(visibility: &Token) -> HirVisibility {
@@ -23,6 +22,8 @@ fn remap_bin_op(bin_op: &Token) -> HirBinOps {
         Token::LT => HirBinOps::LT,
         Token::GT => HirBinOps::GT,
         Token::EQ => HirBinOps::EQ,
+        Token::SHL => HirBinOps::SHL,
+        Token::SHR => HirBinOps::SHR,
         _ => panic!("invalid binary operation")
     }
 }
@@ -30,20 +31,58 @@ fn remap_bin_op(bin_op: &Token) -> HirBinOps {
 fn remap_to_hir_ty_hint<'a>(ast_ty: &ExprTy<'a>) -> HirTyHint<'a> {
     match ast_ty {
         ExprTy::Simple(ty) => HirTyHint::Primitive(ty),
-        _ => panic!("Unsupported ast type")
+        ExprTy::Dyn(trait_name) => HirTyHint::Dyn(trait_name),
+        ExprTy::Tuple(elems) => HirTyHint::Tuple(elems.iter().map(remap_to_hir_ty_hint).collect()),
+        ExprTy::Array { elem_ty, size } => HirTyHint::Array(Box::new(remap_to_hir_ty_hint(elem_ty)), *size),
+        ExprTy::Slice(elem) => HirTyHint::Slice(Box::new(remap_to_hir_ty_hint(elem))),
+        ExprTy::Applied { name, args } => HirTyHint::Applied(name, args.iter().map(remap_to_hir_ty_hint).collect()),
+        ExprTy::Function { params, ret } => HirTyHint::Function(
+            params.iter().map(remap_to_hir_ty_hint).collect(),
+            ret.as_ref().map(|ret| Box::new(remap_to_hir_ty_hint(ret)))
+        ),
     }
 }
 
-fn translate_decls<'a>(expr: &AstExpr<'a>) -> HirExpr<'a> {
+fn translate_pattern<'a>(pattern: &AstPattern<'a>) -> HirPattern<'a> {
+    match pattern {
+        AstPattern::Binding(name) => HirPattern::Binding(name, HirId::new()),
+        AstPattern::Wildcard => HirPattern::Wildcard,
+        AstPattern::Tuple(elems) => HirPattern::Tuple(elems.iter().map(translate_pattern).collect()),
+        AstPattern::Struct { name, fields } => HirPattern::Struct {
+            name,
+            fields: fields.iter().map(|field| (field.0, translate_pattern(&field.1))).collect()
+        },
+    }
+}
+
+// parameters bound by a pattern are unnamed, their bindings are attached to the function
+fn pattern_arg_name<'a>(pattern: &AstPattern<'a>) -> &'a str {
+    match pattern {
+        AstPattern::Binding(name) => name,
+        _ => "_"
+    }
+}
+
+fn translate_decls<'a>(ctx: &GlobalCtx, expr: &AstExpr<'a>) -> HirExpr<'a> {
     match expr {
+        AstExpr::Identifier("None") => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::Variant { variant: HirVariant::None, value: None },
+        },
+
         AstExpr::Identifier(id) => HirExpr { 
             id: HirId::new(), 
             kind: HirExprKind::Id(*id),
         },
 
-        AstExpr::Integer(num) => HirExpr { 
+        AstExpr::Path(alias, name) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::Path { alias, name },
+        },
+
+        AstExpr::Integer(num, negative, suffix) => HirExpr { 
             id: HirId::new(), 
-            kind: HirExprKind::Int(*num),
+            kind: HirExprKind::Int(*num, *negative, *suffix),
         },
 
         AstExpr::Bool(val) => HirExpr { 
@@ -51,35 +90,81 @@ fn translate_decls<'a>(expr: &AstExpr<'a>) -> HirExpr<'a> {
             kind: HirExprKind::Bool(*val),
         },
 
-        AstExpr::Float(val) => HirExpr { 
+        AstExpr::Float(val, suffix) => HirExpr { 
             id: HirId::new(), 
-            kind: HirExprKind::Float(*val),
+            kind: HirExprKind::Float(*val, *suffix),
         },
 
         AstExpr::Block(val) => HirExpr { 
             id: HirId::new(), 
-            kind: HirExprKind::Block(val.iter().map(|expr| translate_decls(expr)).collect()),
+            kind: HirExprKind::Block(val.iter().map(|expr| translate_decls(ctx, expr)).collect()),
         },
 
         AstExpr::Binary(val) => HirExpr { 
             id: HirId::new(), 
             kind: HirExprKind::Binary {
                 op: remap_bin_op(&val.operator),
-                lhs: Box::new(translate_decls(&val.lhs)),
-                rhs: Box::new(translate_decls(&val.rhs))
+                lhs: Box::new(translate_decls(ctx, &val.lhs)),
+                rhs: Box::new(translate_decls(ctx, &val.rhs))
             },
         },
 
         AstExpr::Return(val) => HirExpr { 
             id: HirId::new(), 
-            kind: HirExprKind::Return(val.clone().map(|expr| Box::new(translate_decls(&expr)))),
+            kind: HirExprKind::Return(val.clone().map(|expr| Box::new(translate_decls(ctx, &expr)))),
         },
 
+        AstExpr::String(val) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::Str(val),
+        },
+
+        // `Vec` is built in, its constructor gets the element type from the context it is used in
+        AstExpr::Call(val) if val.alias == Some("Vec") => {
+            let kind = if val.name != "new" {
+                ctx.error(format!("unknown built-in function Vec::{}", val.name));
+                HirExprKind::Error
+            } else if !val.args.is_empty() {
+                ctx.error(format!("Vec::new takes no arguments but {} were given", val.args.len()));
+                HirExprKind::Error
+            } else {
+                HirExprKind::VecNew
+            };
+
+            HirExpr {
+                id: HirId::new(),
+                kind
+            }
+        }
+
+        AstExpr::Call(val) if val.alias.is_none() && matches!(val.name, "Some" | "Ok" | "Err") => {
+            let [value] = val.args.as_slice() else {
+                ctx.error(format!("{} takes exactly one argument but {} were given", val.name, val.args.len()));
+
+                return HirExpr {
+                    id: HirId::new(),
+                    kind: HirExprKind::Error
+                };
+            };
+
+            let variant = match val.name {
+                "Some" => HirVariant::Some,
+                "Ok" => HirVariant::Ok,
+                _ => HirVariant::Err
+            };
+
+            HirExpr {
+                id: HirId::new(),
+                kind: HirExprKind::Variant { variant, value: Some(Box::new(translate_decls(ctx, value))) },
+            }
+        }
+
         AstExpr::Call(val) => HirExpr { 
             id: HirId::new(), 
             kind: HirExprKind::Call {
+                alias: val.alias,
                 name: val.name,
-                args: val.args.iter().map(|expr| translate_decls(expr)).collect(),
+                args: val.args.iter().map(|expr| translate_decls(ctx, expr)).collect(),
             },
         },
 
@@ -96,7 +181,7 @@ fn translate_decls<'a>(expr: &AstExpr<'a>) -> HirExpr<'a> {
                 id: var_id, 
                 kind: HirExprKind::VarDef {
                     name: val.name,
-                    value: Box::new(translate_decls(&val.content)),
+                    value: Box::new(translate_decls(ctx, &val.content)),
                     ty: val_ty
                 }
             }
@@ -105,17 +190,211 @@ fn translate_decls<'a>(expr: &AstExpr<'a>) -> HirExpr<'a> {
         AstExpr::IfExpr(val) => HirExpr { 
             id: HirId::new(), 
             kind: HirExprKind::If { 
-                cond: Box::new(translate_decls( &val.logic_condition)), 
-                then: Box::new(translate_decls(&val.if_block)),
-                _else: val.else_block.clone().map(|expr| Box::new(translate_decls(&expr)))
+                cond: Box::new(translate_decls(ctx,  &val.logic_condition)), 
+                then: Box::new(translate_decls(ctx, &val.if_block)),
+                _else: val.else_block.clone().map(|expr| Box::new(translate_decls(ctx, &expr)))
+            },
+        },
+
+        AstExpr::Cast(val) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::Cast {
+                expr: Box::new(translate_decls(ctx, &val.expr)),
+                ty: remap_to_hir_ty_hint(&val.ty)
+            },
+        },
+
+        AstExpr::Closure(val) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::Closure {
+                args: val.args.iter().map(|arg| {
+                    (arg.0, HirId::new(), remap_to_hir_ty_hint(&arg.1))
+                }).collect(),
+                body: Box::new(translate_decls(ctx, &val.body))
+            },
+        },
+
+        AstExpr::StructLiteral(val) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::StructLiteral {
+                name: val.name,
+                fields: val.fields.iter().map(|field| (field.0, translate_decls(ctx, &field.1))).collect()
+            },
+        },
+
+        AstExpr::FieldAccess(val) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::FieldAccess {
+                expr: Box::new(translate_decls(ctx, &val.expr)),
+                field: val.field
+            },
+        },
+
+        AstExpr::Destructure(val) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::Destructure {
+                pattern: translate_pattern(&val.pattern),
+                value: Box::new(translate_decls(ctx, &val.content)),
+                ty: val.ty.as_ref().map(remap_to_hir_ty_hint)
+            },
+        },
+
+        AstExpr::Array(elems) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::Array(elems.iter().map(|elem| translate_decls(ctx, elem)).collect()),
+        },
+
+        AstExpr::Index(val) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::Index {
+                expr: Box::new(translate_decls(ctx, &val.expr)),
+                index: Box::new(translate_decls(ctx, &val.index))
+            },
+        },
+
+        AstExpr::For(val) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::For {
+                pattern: translate_pattern(&val.pattern),
+                iterable: Box::new(translate_decls(ctx, &val.iterable)),
+                body: Box::new(translate_decls(ctx, &val.body))
+            },
+        },
+
+        AstExpr::Tuple(elems) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::Tuple(elems.iter().map(|elem| translate_decls(ctx, elem)).collect()),
+        },
+
+        AstExpr::TupleIndex(val) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::TupleIndex {
+                expr: Box::new(translate_decls(ctx, &val.expr)),
+                index: val.index
+            },
+        },
+
+        AstExpr::Try(inner) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::Try(Box::new(translate_decls(ctx, inner))),
+        },
+
+        AstExpr::Defer(inner) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::Defer(Box::new(translate_decls(ctx, inner))),
+        },
+
+        AstExpr::MethodCall(val) => HirExpr {
+            id: HirId::new(),
+            kind: HirExprKind::MethodCall {
+                receiver: Box::new(translate_decls(ctx, &val.receiver)),
+                method: val.method,
+                args: val.args.iter().map(|expr| translate_decls(ctx, expr)).collect(),
             },
         },
 
-        _ => panic!("Unsupported expression: {:?}", expr)
     }
 }
 
-pub fn translate_to_hir<'a>(ast: &'a ParsedFile<'a>) -> HirFile<'a> {
+// `Self` is replaced by the implemented type and generic parameters get their bounds attached,
+// so later passes only see resolved type hints
+fn substitute_names<'a>(ctx: &GlobalCtx, hint: HirTyHint<'a>, self_ty: Option<&HirTyHint<'a>>, generics: &[HirGeneric<'a>], fn_name: &str) -> HirTyHint<'a> {
+    match hint {
+        HirTyHint::Primitive("Self") => match self_ty {
+            Some(target) => target.clone(),
+            None => {
+                ctx.error(format!("Self is only available inside impl blocks and traits, but used in {}", fn_name));
+                HirTyHint::Primitive("Self")
+            }
+        },
+        HirTyHint::Primitive(name) => match generics.iter().find(|generic| generic.name == name) {
+            Some(generic) => HirTyHint::Generic(generic.name, generic.bounds.clone()),
+            None => HirTyHint::Primitive(name)
+        },
+        HirTyHint::Array(elem, size) => HirTyHint::Array(Box::new(substitute_names(ctx, *elem, self_ty, generics, fn_name)), size),
+        HirTyHint::Function(params, ret) => HirTyHint::Function(
+            params.into_iter().map(|param| substitute_names(ctx, param, self_ty, generics, fn_name)).collect(),
+            ret.map(|ret| Box::new(substitute_names(ctx, *ret, self_ty, generics, fn_name)))
+        ),
+        HirTyHint::Tuple(elems) => HirTyHint::Tuple(
+            elems.into_iter().map(|elem| substitute_names(ctx, elem, self_ty, generics, fn_name)).collect()
+        ),
+        HirTyHint::Slice(elem) => HirTyHint::Slice(Box::new(substitute_names(ctx, *elem, self_ty, generics, fn_name))),
+        HirTyHint::Applied(name, args) => HirTyHint::Applied(
+            name,
+            args.into_iter().map(|arg| substitute_names(ctx, arg, self_ty, generics, fn_name)).collect()
+        ),
+        hint => hint
+    }
+}
+
+fn translate_function<'a>(ctx: &GlobalCtx, fun: &'a AstFunction<'a>, self_ty: Option<&'a str>) -> HirModuleItem<'a> {
+    let generics: Vec<HirGeneric<'a>> = fun.generics.iter().map(|generic| HirGeneric {
+        name: generic.0,
+        bounds: generic.1.clone()
+    }).collect();
+
+    let self_hint = self_ty.map(HirTyHint::Primitive);
+
+    let args = fun.args.iter().enumerate().map(|(pos, arg)| {
+        let name = pattern_arg_name(&arg.0);
+
+        if name == "self" && pos != 0 {
+            ctx.error(format!("self must be the first parameter of {}", fun.name));
+        }
+
+        (name, HirId::new(), substitute_names(ctx, remap_to_hir_ty_hint(&arg.1), self_hint.as_ref(), &generics, &fun.name))
+    }).collect();
+
+    let patterns = fun.args.iter().enumerate()
+        .filter(|(_, arg)| !matches!(arg.0, AstPattern::Binding(_) | AstPattern::Wildcard))
+        .map(|(pos, arg)| (pos, translate_pattern(&arg.0)))
+        .collect();
+
+    let ret_ty = fun.return_type.as_ref()
+        .map(|ret_t| substitute_names(ctx, remap_to_hir_ty_hint(ret_t), self_hint.as_ref(), &generics, &fun.name));
+
+    HirModuleItem::Func {
+        id: HirId::new(),
+        name: &fun.name,
+        generics,
+        args,
+        patterns,
+        body: translate_decls(ctx, &fun.body),
+        visibility: remap_visibility(&fun.visibility),
+        ret_ty
+    }
+}
+
+// inside a trait `Self` stands for any implementing type
+fn translate_trait<'a>(ctx: &GlobalCtx, tr: &'a AstTrait<'a>) -> HirModuleItem<'a> {
+    let self_hint = HirTyHint::Generic("Self", vec![tr.name]);
+
+    let methods = tr.methods.iter().map(|method| {
+        if method.args.first().is_none_or(|arg| pattern_arg_name(&arg.0) != "self") {
+            ctx.error(format!("trait method {}::{} must take self as its first parameter", tr.name, method.name));
+        }
+
+        HirTraitMethod {
+            id: HirId::new(),
+            name: method.name,
+            args: method.args.iter().map(|arg| {
+                (pattern_arg_name(&arg.0), substitute_names(ctx, remap_to_hir_ty_hint(&arg.1), Some(&self_hint), &[], method.name))
+            }).collect(),
+            ret_ty: method.return_type.as_ref()
+                .map(|ret_t| substitute_names(ctx, remap_to_hir_ty_hint(ret_t), Some(&self_hint), &[], method.name))
+        }
+    }).collect();
+
+    HirModuleItem::Trait {
+        id: HirId::new(),
+        name: tr.name,
+        methods,
+        visibility: remap_visibility(&tr.visibility)
+    }
+}
+
+pub fn translate_to_hir<'a>(ctx: &GlobalCtx, ast: &'a ParsedFile<'a>) -> HirFile<'a> {
     let mut hir = HirFile {
         name: ast.name,
         items: vec![],
@@ -125,19 +404,52 @@ pub fn translate_to_hir<'a>(ast: &'a ParsedFile<'a>) -> HirFile<'a> {
     for decl in &ast.content {
         match decl {
             AstDefinitions::Function(fun) => {
-                let args = fun.args.iter().map(|arg| {
-                    (arg.0, HirId::new(), remap_to_hir_ty_hint(&arg.1))
-                }).collect();
-
-                let func_id = HirId::new();
-
-                hir.items.push(HirModuleItem::Func {
-                    id: func_id,
-                    name: &fun.name,
-                    args,
-                    body: translate_decls(&fun.body),
-                    visibility: remap_visibility(&fun.visibility),
-                    ret_ty: fun.return_type.as_ref().map(|ret_t|remap_to_hir_ty_hint(ret_t))
+                hir.items.push(translate_function(ctx, fun, None));
+            }
+            AstDefinitions::Struct(structure) => {
+                hir.items.push(HirModuleItem::Struct {
+                    id: HirId::new(),
+                    name: structure.name,
+                    fields: structure.fields.iter().map(|field| {
+                        (field.0, remap_to_hir_ty_hint(&field.1))
+                    }).collect(),
+                    visibility: remap_visibility(&structure.visibility)
+                });
+            }
+            AstDefinitions::Impl(imp) => {
+                hir.items.push(HirModuleItem::Impl {
+                    id: HirId::new(),
+                    target: imp.target,
+                    trait_name: imp.trait_name,
+                    methods: imp.methods.iter().map(|fun| translate_function(ctx, fun, Some(imp.target))).collect()
+                });
+            }
+            AstDefinitions::Trait(tr) => {
+                hir.items.push(translate_trait(ctx, tr));
+            }
+            AstDefinitions::TypeAlias(alias) => {
+                hir.items.push(HirModuleItem::TypeAlias {
+                    id: HirId::new(),
+                    name: alias.name,
+                    ty: remap_to_hir_ty_hint(&alias.ty),
+                    distinct: alias.distinct,
+                    visibility: remap_visibility(&alias.visibility)
+                });
+            }
+            AstDefinitions::Const(constant) => {
+                hir.items.push(HirModuleItem::Const {
+                    id: HirId::new(),
+                    name: constant.name,
+                    ty: remap_to_hir_ty_hint(&constant.ty),
+                    value: translate_decls(ctx, &constant.value),
+                    visibility: remap_visibility(&constant.visibility)
+                });
+            }
+            AstDefinitions::Import(import) => {
+                hir.imports.push(HirImport {
+                    path: import.path.clone(),
+                    alias: import.alias,
+                    system: import.target_found == "system"
                 });
             }
             _=> panic!("Unsupported declaration")
@@ -1,12 +1,64 @@
-use module_builder::{build_module, BuildingModule};
+use std::{env, fs, path::Path, process};
 
+use module_builder::{build_module, module_sources, BuildingModule};
+
+const USAGE: &str = "usage: cli [--infer-returns] <project root> <output dir> [files...]";
+
+// the sources default to the .cd files in the project root
 fn main() {
-    build_module(&BuildingModule {
-        name: "test_proj",
-        path: "/Users/ivankaravaev/Documents/Comodot/compiler/test_proj",
-        files: vec![
-            "/Users/ivankaravaev/Documents/Comodot/compiler/test_proj/main.cd",
-            "/Users/ivankaravaev/Documents/Comodot/compiler/test_proj/another_in_module.cd"
-            ]
+    let (flags, args): (Vec<String>, Vec<String>) = env::args().skip(1).partition(|arg| arg.starts_with("--"));
+
+    let infer_return_types = flags.iter().any(|flag| flag == "--infer-returns");
+
+    if let Some(flag) = flags.iter().find(|flag| *flag != "--infer-returns") {
+        eprintln!("error: unknown option {}\n{}", flag, USAGE);
+        process::exit(1);
+    }
+
+    let [root, out_dir, files @ ..] = args.as_slice() else {
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    };
+
+    let root_path = Path::new(root);
+
+    let name = root_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("main")
+        .to_string();
+
+    let files: Vec<String> = if files.is_empty() {
+        module_sources(root_path).iter().map(|file| file.display().to_string()).collect()
+    } else {
+        files.to_vec()
+    };
+
+    if files.is_empty() {
+        eprintln!("error: no .cd sources found in {}", root);
+        process::exit(1);
+    }
+
+    let objects = build_module(&BuildingModule {
+        name: &name,
+        path: root,
+        root,
+        files: files.iter().map(String::as_str).collect(),
+        infer_return_types
     });
-}
\ No newline at end of file
+
+    fs::create_dir_all(out_dir).unwrap_or_else(|err| {
+        eprintln!("error: can not create {}: {}", out_dir, err);
+        process::exit(1);
+    });
+
+    // every imported module gets an object of its own, all of them have to be linked together
+    for object in objects {
+        let path = Path::new(out_dir).join(format!("{}.o", object.name));
+
+        fs::write(&path, &object.code).unwrap_or_else(|err| {
+            eprintln!("error: can not write {}: {}", path.display(), err);
+            process::exit(1);
+        });
+    }
+}
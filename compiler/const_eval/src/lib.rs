@@ -0,0 +1,256 @@
+use std::collections::{HashMap, HashSet};
+
+use hir::{HirBinOps, HirExpr, HirExprKind, HirId, HirModuleItem};
+use middle::{ty::{LangType, Primitive}, ConstValue, GlobalCtx};
+
+struct ConstEvaluator<'a, 'ctx> {
+    ctx: &'ctx GlobalCtx<'a>,
+    decls: HashMap<HirId, (&'a str, &'ctx HirExpr<'a>)>,
+    in_progress: HashSet<HirId>,
+    // constants whose evaluation reported an error, their users are not reported again
+    failed: HashSet<HirId>,
+}
+
+impl<'a, 'ctx> ConstEvaluator<'a, 'ctx> {
+    fn new(ctx: &'ctx GlobalCtx<'a>) -> Self {
+        let mut decls = HashMap::new();
+
+        for file in &ctx.module_files {
+            for item in &file.items {
+                if let HirModuleItem::Const { id, name, value, .. } = item {
+                    decls.insert(*id, (*name, value));
+                }
+            }
+        }
+
+        Self {
+            ctx,
+            decls,
+            in_progress: HashSet::new(),
+            failed: HashSet::new(),
+        }
+    }
+
+    fn eval_const(&mut self, id: HirId) -> Option<ConstValue> {
+        if let Some(value) = self.ctx.module_consts.borrow().get_value(&id) {
+            return Some(*value);
+        }
+
+        if self.failed.contains(&id) {
+            return None;
+        }
+
+        let (name, value) = self.decls[&id];
+
+        if !self.in_progress.insert(id) {
+            self.ctx.error(format!("constant {} depends on itself", name));
+            self.failed.insert(id);
+            return None;
+        }
+
+        let result = self.eval_expr(name, value).filter(|result| self.check_range(id, name, result));
+
+        self.in_progress.remove(&id);
+
+        match result {
+            Some(result) => self.ctx.module_consts.borrow_mut().insert_value(id, result),
+            None => {
+                self.failed.insert(id);
+            }
+        }
+
+        result
+    }
+
+    // the folded value has to fit the declared type, like a literal of that type would
+    fn check_range(&self, id: HirId, name: &str, value: &ConstValue) -> bool {
+        let ty = self.ctx.module_ty_info.borrow().get_type(&id).unwrap().ty.clone();
+
+        let LangType::Primitives(primitive) = ty.underlying() else {
+            return true;
+        };
+
+        let fits = match value {
+            ConstValue::Int(val) if primitive.is_integer() => primitive.fits_integer((*val).into()),
+            ConstValue::Float(val) => primitive.fits_float(*val),
+            _ => true
+        };
+
+        if !fits {
+            self.ctx.error(format!("constant {} has value {} which does not fit in {}", name, value, primitive.name()));
+        }
+
+        fits
+    }
+
+    // `const_name` is the constant being evaluated, an error is reported once and makes the result `None`
+    fn eval_expr(&mut self, const_name: &str, expr: &HirExpr<'a>) -> Option<ConstValue> {
+        match &expr.kind {
+            HirExprKind::Int(val, negative, _) => {
+                let val = i128::try_from(*val).ok()
+                    .and_then(|val| i64::try_from(if *negative { -val } else { val }).ok());
+
+                if val.is_none() {
+                    self.ctx.error(format!("constant {} uses a literal beyond the range of Int, which constants are computed in", const_name));
+                }
+
+                val.map(ConstValue::Int)
+            }
+            HirExprKind::Float(val, _) => Some(ConstValue::Float(*val)),
+            HirExprKind::Bool(val) => Some(ConstValue::Bool(*val)),
+            HirExprKind::Char(val) => Some(ConstValue::Char(*val)),
+
+            // a name the resolver could not find was reported by it, imported constants come with their value
+            HirExprKind::Id(name) | HirExprKind::Path { name, .. } => {
+                let symbol = self.ctx.module_symbols.borrow().get_pair(&expr.id).cloned()?;
+
+                if let Some(value) = self.ctx.module_consts.borrow().get_value(&symbol.id) {
+                    return Some(*value);
+                }
+
+                if !self.decls.contains_key(&symbol.id) {
+                    self.ctx.error(format!("{} is not a constant and can not be used in constant {}", name, const_name));
+                    return None;
+                }
+
+                self.eval_const(symbol.id)
+            }
+
+            HirExprKind::Binary { op, lhs, rhs } => {
+                let lhs = self.eval_expr(const_name, lhs)?;
+                let rhs = self.eval_expr(const_name, rhs)?;
+
+                self.report(const_name, eval_binary(op, lhs, rhs))
+            }
+
+            HirExprKind::Cast { expr: inner, ty: _ } => {
+                let value = self.eval_expr(const_name, inner)?;
+                let target = self.ctx.module_ty_info.borrow().get_type(&expr.id).unwrap().ty.clone();
+
+                self.report(const_name, eval_cast(value, &target))
+            }
+
+            HirExprKind::Call { name, .. } => {
+                self.ctx.error(format!("call of {} is not allowed in constant {}", name, const_name));
+                None
+            }
+
+            _ => {
+                self.ctx.error(format!("constant {} can only be computed from literals, other constants, operators and casts", const_name));
+                None
+            }
+        }
+    }
+
+    fn report(&self, const_name: &str, result: Result<ConstValue, String>) -> Option<ConstValue> {
+        result.map_err(|message| self.ctx.error(format!("{} in constant {}", message, const_name))).ok()
+    }
+}
+
+fn wrap_to_width(value: i64, primitive: &Primitive) -> i64 {
+    let width = primitive.bit_width();
+
+    if width >= 64 {
+        return value;
+    }
+
+    let shift = 64 - width;
+
+    if primitive.is_unsigned() {
+        ((value as u64) << shift >> shift) as i64
+    } else {
+        (value << shift) >> shift
+    }
+}
+
+fn eval_cast(value: ConstValue, target: &LangType) -> Result<ConstValue, String> {
+    let LangType::Primitives(primitive) = target.underlying() else {
+        return Err(format!("invalid cast to {}", target));
+    };
+
+    Ok(match value {
+        ConstValue::Int(val) if primitive.is_integer() => ConstValue::Int(wrap_to_width(val, primitive)),
+        ConstValue::Int(val) if primitive.is_float() => ConstValue::Float(val as f64),
+        ConstValue::Float(val) if primitive.is_integer() => ConstValue::Int(wrap_to_width(val as i64, primitive)),
+        ConstValue::Float(val) if primitive.is_float() => ConstValue::Float(val),
+        ConstValue::Bool(val) if primitive.is_integer() => ConstValue::Int(val as i64),
+        ConstValue::Char(val) if primitive.is_integer() => ConstValue::Int(wrap_to_width(val as i64, primitive)),
+        _ => return Err(format!("invalid cast of {} to {}", value, target))
+    })
+}
+
+fn eval_binary(op: &HirBinOps, lhs: ConstValue, rhs: ConstValue) -> Result<ConstValue, String> {
+    let value = match (lhs, rhs) {
+        (ConstValue::Int(l), ConstValue::Int(r)) => {
+            let result = match op {
+                HirBinOps::SUM => l.checked_add(r),
+                HirBinOps::SUB => l.checked_sub(r),
+                HirBinOps::MUL => l.checked_mul(r),
+                HirBinOps::DIV => {
+                    if r == 0 {
+                        return Err("division by zero".to_string());
+                    }
+                    l.checked_div(r)
+                }
+                HirBinOps::SHL => u32::try_from(r).ok().and_then(|r| l.checked_shl(r)),
+                HirBinOps::SHR => u32::try_from(r).ok().and_then(|r| l.checked_shr(r)),
+                HirBinOps::LT => return Ok(ConstValue::Bool(l < r)),
+                HirBinOps::GT => return Ok(ConstValue::Bool(l > r)),
+                HirBinOps::EQ => return Ok(ConstValue::Bool(l == r)),
+                _ => return Err(format!("invalid operation {:?} on integers", op))
+            };
+
+            ConstValue::Int(result.ok_or("overflow")?)
+        }
+
+        (ConstValue::Float(l), ConstValue::Float(r)) => match op {
+            HirBinOps::SUM => ConstValue::Float(l + r),
+            HirBinOps::SUB => ConstValue::Float(l - r),
+            HirBinOps::MUL => ConstValue::Float(l * r),
+            HirBinOps::DIV => ConstValue::Float(l / r),
+            HirBinOps::LT => ConstValue::Bool(l < r),
+            HirBinOps::GT => ConstValue::Bool(l > r),
+            HirBinOps::EQ => ConstValue::Bool(l == r),
+            _ => return Err(format!("invalid operation {:?} on floats", op))
+        },
+
+        (ConstValue::Bool(l), ConstValue::Bool(r)) => match op {
+            HirBinOps::AND => ConstValue::Bool(l && r),
+            HirBinOps::OR => ConstValue::Bool(l || r),
+            HirBinOps::EQ => ConstValue::Bool(l == r),
+            _ => return Err(format!("invalid operation {:?} on bools", op))
+        },
+
+        (ConstValue::Char(l), ConstValue::Char(r)) => match op {
+            HirBinOps::LT => ConstValue::Bool(l < r),
+            HirBinOps::GT => ConstValue::Bool(l > r),
+            HirBinOps::EQ => ConstValue::Bool(l == r),
+            _ => return Err(format!("invalid operation {:?} on chars", op))
+        },
+
+        _ => return Err(format!("mismatched operands {} and {}", lhs, rhs))
+    };
+
+    Ok(value)
+}
+
+fn evaluate_all<'a>(ctx: &GlobalCtx<'a>) {
+    let mut evaluator = ConstEvaluator::new(ctx);
+
+    for file in &ctx.module_files {
+        for item in &file.items {
+            if let HirModuleItem::Const { id, .. } = item {
+                evaluator.eval_const(*id);
+            }
+        }
+    }
+}
+
+pub fn evaluate_consts<'a>(ctx: &GlobalCtx<'a>) {
+    evaluate_all(ctx);
+
+    ctx.abort_on_errors();
+}
+
+#[cfg(test)]
+mod tests;
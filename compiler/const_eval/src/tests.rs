@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use ast_lowering::translate_to_hir;
+use hir::HirModuleItem;
+use hir_resolver::resolve_module;
+use middle::{BuildType, ConstValue, GlobalCtx, ModuleInterface};
+use parser::parse_file;
+use types_lowering::type_hir_module;
+
+use crate::evaluate_all;
+
+fn analyze(name: &'static str, source: &'static str, interfaces: Vec<ModuleInterface>) -> GlobalCtx<'static> {
+    let parsed = Box::leak(Box::new(parse_file(name, source)));
+
+    let mut ctx = GlobalCtx::new(name.to_string(), "x86_64".to_string(), BuildType::ModulePack);
+    ctx.module_interfaces = interfaces;
+    ctx.module_files.push(translate_to_hir(&ctx, parsed));
+
+    resolve_module(&mut ctx);
+    type_hir_module(&mut ctx);
+    assert_eq!(*ctx.module_errors.borrow(), Vec::<String>::new());
+
+    evaluate_all(&ctx);
+
+    ctx
+}
+
+fn values_of(ctx: &GlobalCtx) -> HashMap<String, ConstValue> {
+    let consts = ctx.module_consts.borrow();
+
+    ctx.module_files.iter()
+        .flat_map(|file| file.items.iter())
+        .filter_map(|item| match item {
+            HirModuleItem::Const { id, name, .. } => Some((name.to_string(), *consts.get_value(id)?)),
+            _ => None
+        })
+        .collect()
+}
+
+fn const_values(source: &'static str) -> HashMap<String, ConstValue> {
+    let ctx = analyze("test", source, Vec::new());
+    assert_eq!(*ctx.module_errors.borrow(), Vec::<String>::new());
+
+    values_of(&ctx)
+}
+
+fn const_errors(source: &'static str) -> Vec<String> {
+    analyze("test", source, Vec::new()).module_errors.take()
+}
+
+#[test]
+fn folds_arithmetic_over_other_constants() {
+    let values = const_values("const MAX: Int = BASE * 2 + 24;\nconst BASE: Int = 1000;\nconst BIG: Bool = MAX > BASE;");
+
+    assert_eq!(values["MAX"], ConstValue::Int(2024));
+    assert_eq!(values["BIG"], ConstValue::Bool(true));
+}
+
+#[test]
+fn folds_float_constants() {
+    let values = const_values("const HALF: Float = 1.0 / 2.0;");
+
+    assert_eq!(values["HALF"], ConstValue::Float(0.5));
+}
+
+#[test]
+fn wraps_casts_to_the_width_of_the_target() {
+    let values = const_values("const BYTE: U8 = 300 as U8;\nconst SIGNED: I8 = 200 as I8;");
+
+    assert_eq!(values["BYTE"], ConstValue::Int(44));
+    assert_eq!(values["SIGNED"], ConstValue::Int(-56));
+}
+
+#[test]
+fn reports_overflow() {
+    assert_eq!(const_errors("const HUGE: Int = 9223372036854775807 + 1;"), ["overflow in constant HUGE"]);
+}
+
+#[test]
+fn reports_division_by_zero() {
+    assert_eq!(const_errors("const ZERO: Int = 0;\nconst BAD: Int = 1 / ZERO;"), ["division by zero in constant BAD"]);
+}
+
+#[test]
+fn reports_cyclic_constants_once() {
+    assert_eq!(const_errors("const A: Int = B;\nconst B: Int = A + 1;"), ["constant A depends on itself"]);
+}
+
+#[test]
+fn reports_values_out_of_the_declared_range() {
+    assert_eq!(const_errors("const BYTE: U8 = 200 + 100;"), ["constant BYTE has value 300 which does not fit in U8"]);
+    assert_eq!(const_errors("const SMALL: I8 = 100 + 100;"), ["constant SMALL has value 200 which does not fit in I8"]);
+}
+
+#[test]
+fn reports_calls_and_non_constant_names() {
+    let errors = const_errors("fn seven() -> Int { ret 7; }\nconst CALLED: Int = seven();\nconst NAMED: Int = seven;");
+
+    assert_eq!(errors, [
+        "call of seven is not allowed in constant CALLED",
+        "seven is not a constant and can not be used in constant NAMED"
+    ]);
+}
+
+#[test]
+fn uses_public_constants_of_imported_modules() {
+    let util = analyze("util", "pub const BASE: Int = 40;\nconst HIDDEN: Int = 1;", Vec::new());
+
+    let main = analyze("main", "import util\nconst ANSWER: Int = util::BASE + 2;", vec![util.interface()]);
+
+    assert_eq!(values_of(&main)["ANSWER"], ConstValue::Int(42));
+}
@@ -20,6 +20,38 @@ pub enum HirVisibility {
 pub enum HirTyHint<'a> {
     Primitive(&'a str),
     Array(Box<HirTyHint<'a>>, usize),
+    Function(Vec<HirTyHint<'a>>, Option<Box<HirTyHint<'a>>>),
+    Generic(&'a str, Vec<&'a str>),
+    Dyn(&'a str),
+    Tuple(Vec<HirTyHint<'a>>),
+    Slice(Box<HirTyHint<'a>>),
+    Applied(&'a str, Vec<HirTyHint<'a>>),
+}
+
+// names bound by a pattern get their own ids, like variables and parameters
+#[derive(Debug, Clone)]
+pub enum HirPattern<'a> {
+    Binding(&'a str, HirId),
+    Wildcard,
+    Tuple(Vec<HirPattern<'a>>),
+    Struct {
+        name: &'a str,
+        fields: Vec<(&'a str, HirPattern<'a>)>
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct HirGeneric<'a> {
+    pub name: &'a str,
+    pub bounds: Vec<&'a str>,
+}
+
+#[derive(Debug)]
+pub struct HirTraitMethod<'a> {
+    pub id: HirId,
+    pub name: &'a str,
+    pub args: Vec<(&'a str, HirTyHint<'a>)>,
+    pub ret_ty: Option<HirTyHint<'a>>,
 }
 
 #[derive(Debug)]
@@ -33,6 +65,8 @@ pub struct HirFile<'a> {
 pub struct HirImport<'a> {
     pub path: Vec<&'a str>,  
     pub alias: Option<&'a str>,
+    // found among the installed system modules instead of the project directories
+    pub system: bool
 }
 
 #[derive(Debug)]
@@ -40,10 +74,44 @@ pub enum HirModuleItem <'a> {
     Func {
         id: HirId,
         name: &'a str,
+        generics: Vec<HirGeneric<'a>>,
         args: Vec<(&'a str, HirId, HirTyHint<'a>)>,
+        patterns: Vec<(usize, HirPattern<'a>)>,
         body: HirExpr<'a>,
         ret_ty: Option<HirTyHint<'a>>,
         visibility: HirVisibility
+    },
+    Const {
+        id: HirId,
+        name: &'a str,
+        ty: HirTyHint<'a>,
+        value: HirExpr<'a>,
+        visibility: HirVisibility
+    },
+    Struct {
+        id: HirId,
+        name: &'a str,
+        fields: Vec<(&'a str, HirTyHint<'a>)>,
+        visibility: HirVisibility
+    },
+    Impl {
+        id: HirId,
+        target: &'a str,
+        trait_name: Option<&'a str>,
+        methods: Vec<HirModuleItem<'a>>
+    },
+    Trait {
+        id: HirId,
+        name: &'a str,
+        methods: Vec<HirTraitMethod<'a>>,
+        visibility: HirVisibility
+    },
+    TypeAlias {
+        id: HirId,
+        name: &'a str,
+        ty: HirTyHint<'a>,
+        distinct: bool,
+        visibility: HirVisibility
     }
 }
 
@@ -63,23 +131,83 @@ pub enum HirBinOps {
     OR,
     LT,
     GT,
-    EQ
+    EQ,
+    SHL,
+    SHR
 } 
 
+// constructors of the built-in `Option` and `Result` types
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HirVariant {
+    Some,
+    None,
+    Ok,
+    Err
+}
+
 #[derive(Debug, Clone)]
 pub enum HirExprKind<'a> {
     Id(&'a str),
-    Int(i64),
-    Float(f64),
+    // the value without its sign, whether it is negative and the suffix
+    Int(u128, bool, Option<&'a str>),
+    Float(f64, Option<&'a str>),
     Bool(bool),
     Char(char),
+    Str(&'a str),
 
     Block(Vec<HirExpr<'a>>),
 
+    // `alias::name`, a constant of an imported module
+    Path {
+        alias: &'a str,
+        name: &'a str
+    },
+
     Call {
+        alias: Option<&'a str>,
         name: &'a str,
         args: Vec<HirExpr<'a>>,
     },
+    MethodCall {
+        receiver: Box<HirExpr<'a>>,
+        method: &'a str,
+        args: Vec<HirExpr<'a>>,
+    },
+    StructLiteral {
+        name: &'a str,
+        fields: Vec<(&'a str, HirExpr<'a>)>
+    },
+    FieldAccess {
+        expr: Box<HirExpr<'a>>,
+        field: &'a str
+    },
+    Destructure {
+        pattern: HirPattern<'a>,
+        value: Box<HirExpr<'a>>,
+        ty: Option<HirTyHint<'a>>
+    },
+    Array(Vec<HirExpr<'a>>),
+    Index {
+        expr: Box<HirExpr<'a>>,
+        index: Box<HirExpr<'a>>
+    },
+    For {
+        pattern: HirPattern<'a>,
+        iterable: Box<HirExpr<'a>>,
+        body: Box<HirExpr<'a>>
+    },
+    VecNew,
+    Variant {
+        variant: HirVariant,
+        value: Option<Box<HirExpr<'a>>>
+    },
+    Try(Box<HirExpr<'a>>),
+    Defer(Box<HirExpr<'a>>),
+    Tuple(Vec<HirExpr<'a>>),
+    TupleIndex {
+        expr: Box<HirExpr<'a>>,
+        index: usize
+    },
     Binary {
         op: HirBinOps,
         lhs: Box<HirExpr<'a>>,
@@ -95,5 +223,25 @@ pub enum HirExprKind<'a> {
         name: &'a str,
         value: Box<HirExpr<'a>>,
         ty: Option<HirTyHint<'a>>
+    },
+    Cast {
+        expr: Box<HirExpr<'a>>,
+        ty: HirTyHint<'a>
+    },
+    Closure {
+        args: Vec<(&'a str, HirId, HirTyHint<'a>)>,
+        body: Box<HirExpr<'a>>
+    },
+    // an expression that could not be lowered, it has been reported already
+    Error
+}
+// whether control can not reach the end of the expression without leaving the function, such an
+// expression never produces a value, so it fits wherever a value of any type is expected
+pub fn diverges(expr: &HirExpr) -> bool {
+    match &expr.kind {
+        HirExprKind::Return(_) => true,
+        HirExprKind::Block(exprs) => exprs.iter().any(diverges),
+        HirExprKind::If { cond: _, then, _else: Some(_else) } => diverges(then) && diverges(_else),
+        _ => false
     }
-}
\ No newline at end of file
+}
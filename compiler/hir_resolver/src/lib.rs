@@ -1,16 +1,25 @@
-use std::{collections::HashMap};
+use std::collections::{HashMap, HashSet};
 
-use hir::{HirExpr, HirExprKind, HirModuleItem, HirVisibility};
-use middle::{GlobalCtx, SymbolInfo};
+use hir::{HirExpr, HirExprKind, HirGeneric, HirId, HirModuleItem, HirPattern, HirTyHint, HirVisibility};
+use middle::{mangle, ty::{LangType, Primitive}, BuildType, Capture, GlobalCtx, MethodInfo, SymbolInfo, SymbolKind, TraitMethodInfo, TypeInfo};
 
 struct Env<'a> {
-    scopes: Vec<HashMap<&'a str, Vec<SymbolInfo>>>
+    scopes: Vec<HashMap<&'a str, Vec<SymbolInfo>>>,
+    // enclosing closures with the index of the first scope that belongs to them
+    closures: Vec<(HirId, usize)>,
+    // modules imported by the file being resolved, by the name calls qualify them with
+    imports: HashMap<&'a str, String>,
+    // generic parameters of the function being resolved
+    type_params: Vec<&'a str>
 }
 
 impl <'a> Env<'a> {
     pub fn new() -> Self {
         let mut global = Self {
-            scopes: vec![]
+            scopes: vec![],
+            closures: vec![],
+            imports: HashMap::new(),
+            type_params: Vec::new()
         };
         
         global.push_scope();
@@ -29,33 +38,466 @@ impl <'a> Env<'a> {
             .push(value);  
     }
     
-    pub fn lookup(&self, name: &'a str) -> Option<SymbolInfo> {
-        for scope in self.scopes.iter().rev() {
+    pub fn lookup_with_depth(&self, name: &'a str) -> Option<(SymbolInfo, usize)> {
+        for (depth, scope) in self.scopes.iter().enumerate().rev() {
             if let Some(values) = scope.get(name) {
-                return values.last().cloned();  
+                return values.last().cloned().map(|symbol| (symbol, depth));  
             }
         }
         None
     }
+
+    // the names in scope whose innermost definition is of a wanted kind
+    pub fn names<'s>(&'s self, wanted: impl Fn(SymbolKind) -> bool + 's) -> impl Iterator<Item = &'a str> + 's {
+        self.scopes.iter()
+            .flat_map(|scope| scope.iter())
+            .filter(move |(_, symbols)| symbols.last().is_some_and(|symbol| wanted(symbol.kind)))
+            .map(|(name, _)| *name)
+    }
+
+    pub fn enter_closure(&mut self, closure_id: HirId) {
+        self.push_scope();
+        self.closures.push((closure_id, self.scopes.len() - 1));
+    }
+
+    pub fn leave_closure(&mut self) {
+        self.closures.pop();
+        self.pop_scope();
+    }
+}
+
+fn lookup_local<'a>(env: &Env<'a>, ctx: &GlobalCtx<'a>, name: &'a str) -> Option<SymbolInfo> {
+    let (symbol, depth) = env.lookup_with_depth(name)?;
+
+    // module level names are reachable from everywhere, everything else
+    // defined outside of a closure has to be captured by it
+    if depth > 0 {
+        for (closure_id, closure_depth) in env.closures.iter() {
+            if depth < *closure_depth {
+                ctx.module_captures.borrow_mut().add_capture(*closure_id, Capture {
+                    name,
+                    id: symbol.id
+                });
+            }
+        }
+    }
+
+    Some(symbol)
+}
+
+fn check_bounds(ctx: &GlobalCtx, fn_name: &str, generics: &[HirGeneric]) {
+    for generic in generics {
+        for bound in &generic.bounds {
+            if !ctx.module_traits.borrow().contains(bound) {
+                ctx.error(format!("unknown trait {} in bounds of {} in {}", bound, generic.name, fn_name));
+            }
+        }
+    }
+}
+
+// functions and constants share one namespace across all files of a module, structs, traits
+// and type aliases another one and methods one per type, while imports only apply to the file that contains them
+fn check_duplicate_definitions(ctx: &GlobalCtx) {
+    // every definition is remembered with its kind and its position, a file and the 1-based index of the item in it
+    let mut values: HashMap<String, (&str, String)> = HashMap::new();
+    let mut types: HashMap<String, (&str, String)> = HashMap::new();
+    let mut methods: HashMap<String, String> = HashMap::new();
+
+    for file in &ctx.module_files {
+        let mut aliases = HashMap::new();
+
+        for import in file.imports.iter().filter(|import| !import.system) {
+            let alias = import.alias.unwrap_or(import.path.last().unwrap());
+
+            if let Some(first) = aliases.insert(alias, import.path.join(".")) {
+                ctx.error(format!("{} is imported more than once in {}: as module {} and as module {}", 
+                    alias, file.name, first, import.path.join(".")));
+            }
+        }
+
+        for (index, item) in file.items.iter().enumerate() {
+            let position = format!("{}, item {}", file.name, index + 1);
+
+            let (namespace, kind, name) = match item {
+                HirModuleItem::Func { name, .. } => (&mut values, "function", name.to_string()),
+                HirModuleItem::Const { name, .. } => (&mut values, "constant", name.to_string()),
+                HirModuleItem::Struct { name, .. } => (&mut types, "struct", name.to_string()),
+                HirModuleItem::Trait { name, .. } => (&mut types, "trait", name.to_string()),
+                HirModuleItem::TypeAlias { name, distinct: false, .. } => (&mut types, "type alias", name.to_string()),
+                HirModuleItem::TypeAlias { name, distinct: true, .. } => (&mut types, "newtype", name.to_string()),
+
+                HirModuleItem::Impl { target, methods: impl_methods, .. } => {
+                    for (method_index, method) in impl_methods.iter().enumerate() {
+                        if let HirModuleItem::Func { name, .. } = method {
+                            let name = format!("{}::{}", target, name);
+                            let method_position = format!("{}, method {}", position, method_index + 1);
+
+                            if let Some(first) = methods.get(&name) {
+                                ctx.error(format!("method {} is defined more than once: at {} and at {}", name, first, method_position));
+                            } else {
+                                methods.insert(name, method_position);
+                            }
+                        }
+                    }
+
+                    continue;
+                }
+            };
+
+            // the first definition stays the one later duplicates are reported against
+            if let Some((first_kind, first_position)) = namespace.get(&name) {
+                ctx.error(format!("{} is defined more than once: as {} at {} and as {} at {}", 
+                    name, first_kind, first_position, kind, position));
+            } else {
+                namespace.insert(name, (kind, position));
+            }
+        }
+    }
+}
+
+// optimal string alignment distance, a swap of two neighbouring characters counts as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut dist = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in dist.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for (j, cell) in dist[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+
+            dist[i][j] = (dist[i - 1][j] + 1).min(dist[i][j - 1] + 1).min(dist[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                dist[i][j] = dist[i][j].min(dist[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    dist[a.len()][b.len()]
+}
+
+// the known name closest to a misspelled one, if it is close enough to be what was meant
+// the distance has to stay below the length of the name, or every short name would be within reach of any other
+fn suggest<'b>(name: &str, candidates: impl Iterator<Item = &'b str>) -> Option<&'b str> {
+    let len = name.chars().count();
+    let limit = (len / 3).max(1);
+
+    candidates
+        .map(|candidate| (edit_distance(&name.to_lowercase(), &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= limit && *distance < len)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn unknown_name_message(kind: &str, name: &str, suggestion: Option<&str>) -> String {
+    match suggestion {
+        Some(suggestion) => format!("unknown {} {}, did you mean {}?", kind, name, suggestion),
+        None => format!("unknown {} {}", kind, name)
+    }
+}
+
+// a misspelled call is most likely another function in scope, otherwise it may be a function of an imported
+// module that was called without the name of its module, a misspelled variable is only ever another value
+fn suggest_name(env: &Env, ctx: &GlobalCtx, name: &str, call: bool) -> Option<String> {
+    if let Some(suggestion) = suggest(name, env.names(|kind| (kind == SymbolKind::Function) == call)) {
+        return Some(suggestion.to_string());
+    }
+
+    if !call {
+        return None;
+    }
+
+    env.imports.iter().find_map(|(alias, module)| {
+        let interface = ctx.module_interfaces.iter().find(|interface| interface.name == *module)?;
+        let functions = interface.functions.iter().filter(|function| function.public);
+
+        suggest(name, functions.map(|function| function.name.as_str()))
+            .map(|function| format!("{}::{}", alias, function))
+    })
+}
+
+// every name used as a type has to be a primitive, a struct, a type alias or a generic parameter in scope
+fn check_type_hint(ctx: &GlobalCtx, hint: &HirTyHint, type_params: &[&str]) {
+    match hint {
+        HirTyHint::Primitive(name) => {
+            let known = Primitive::from_name(name).is_some()
+                || *name == "Self"
+                || type_params.contains(name)
+                || ctx.module_structs.borrow().contains(name)
+                || ctx.module_type_aliases.borrow().contains(name);
+
+            if known {
+                return;
+            }
+
+            let structs = ctx.module_structs.borrow();
+            let aliases = ctx.module_type_aliases.borrow();
+
+            let candidates = Primitive::NAMES.iter().copied()
+                .chain(type_params.iter().copied())
+                .chain(structs.iter().map(|(name, _)| *name))
+                .chain(aliases.iter().map(|(name, _)| *name));
+
+            ctx.error(unknown_name_message("type", name, suggest(name, candidates)));
+        }
+
+        HirTyHint::Applied(name, args) => {
+            const APPLIED: [&str; 3] = ["Vec", "Option", "Result"];
+
+            if !APPLIED.contains(name) {
+                ctx.error(unknown_name_message("generic type", name, suggest(name, APPLIED.iter().copied())));
+            }
+
+            for arg in args {
+                check_type_hint(ctx, arg, type_params);
+            }
+        }
+
+        HirTyHint::Array(elem, _) | HirTyHint::Slice(elem) => check_type_hint(ctx, elem, type_params),
+
+        HirTyHint::Tuple(elems) => {
+            for elem in elems {
+                check_type_hint(ctx, elem, type_params);
+            }
+        }
+
+        HirTyHint::Function(params, ret) => {
+            for param in params {
+                check_type_hint(ctx, param, type_params);
+            }
+
+            if let Some(ret) = ret {
+                check_type_hint(ctx, ret, type_params);
+            }
+        }
+
+        HirTyHint::Generic(..) | HirTyHint::Dyn(_) => ()
+    }
+}
+
+fn alias_references<'a>(hint: &HirTyHint<'a>, names: &mut Vec<&'a str>) {
+    match hint {
+        HirTyHint::Primitive(name) => names.push(name),
+        HirTyHint::Array(elem, _) | HirTyHint::Slice(elem) => alias_references(elem, names),
+        HirTyHint::Tuple(elems) | HirTyHint::Applied(_, elems) => {
+            for elem in elems {
+                alias_references(elem, names);
+            }
+        }
+        HirTyHint::Function(params, ret) => {
+            for param in params {
+                alias_references(param, names);
+            }
+
+            if let Some(ret) = ret {
+                alias_references(ret, names);
+            }
+        }
+        HirTyHint::Generic(..) | HirTyHint::Dyn(_) => ()
+    }
+}
+
+// aliases are expanded where they are used, so none may contain itself, structs are referred to by name and may
+fn check_alias_cycles(ctx: &GlobalCtx) {
+    let mut aliases = HashMap::new();
+
+    for file in &ctx.module_files {
+        for item in &file.items {
+            if let HirModuleItem::TypeAlias { name, ty, .. } = item {
+                let mut names = Vec::new();
+                alias_references(ty, &mut names);
+                aliases.insert(*name, names);
+            }
+        }
+    }
+
+    fn visit<'a>(ctx: &GlobalCtx, aliases: &HashMap<&'a str, Vec<&'a str>>, name: &'a str, path: &mut Vec<&'a str>, visited: &mut HashSet<&'a str>) {
+        if let Some(start) = path.iter().position(|seen| *seen == name) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(name);
+            ctx.error(format!("type {} refers to itself: {}", name, cycle.join(" -> ")));
+
+            // the aliases of the cycle can not be expanded, uses of them are typed as errors
+            for alias in &path[start..] {
+                ctx.module_type_aliases.borrow_mut().set_type(alias, LangType::Error);
+            }
+
+            return;
+        }
+
+        if visited.contains(name) {
+            return;
+        }
+
+        let Some(references) = aliases.get(name) else {
+            return;
+        };
+
+        path.push(name);
+
+        for reference in references {
+            visit(ctx, aliases, reference, path, visited);
+        }
+
+        path.pop();
+        visited.insert(name);
+    }
+
+    let mut names: Vec<&str> = aliases.keys().copied().collect();
+    names.sort();
+
+    let mut visited = HashSet::new();
+
+    for name in names {
+        visit(ctx, &aliases, name, &mut Vec::new(), &mut visited);
+    }
+}
+
+// parameters are given with their 1-based position, names bound by a pattern share the position of the pattern
+fn check_unique_params(ctx: &GlobalCtx, fn_name: &str, names: &[(&str, usize)]) {
+    for (index, (name, position)) in names.iter().enumerate() {
+        if let Some((_, first)) = names[..index].iter().find(|earlier| earlier.0 == *name) {
+            ctx.error(format!("parameter {} of {} is defined more than once, at positions {} and {}", name, fn_name, first, position));
+        }
+    }
+}
+
+// items that were reported as invalid are dropped, so that later passes do not trip over them
+fn remove_items(ctx: &mut GlobalCtx, poisoned: &[HirId]) {
+    for file in &mut ctx.module_files {
+        file.items.retain(|item| !matches!(item,
+            HirModuleItem::Func { id, .. } | HirModuleItem::Const { id, .. } | HirModuleItem::Struct { id, .. }
+            | HirModuleItem::Impl { id, .. } | HirModuleItem::Trait { id, .. } | HirModuleItem::TypeAlias { id, .. }
+            if poisoned.contains(id)
+        ));
+    }
 }
 
 fn track_global_names<'a>(env: &mut Env<'a>, ctx: &mut GlobalCtx<'a>) {
+    check_duplicate_definitions(ctx);
+
+    let mut poisoned = Vec::new();
+
     for file in &ctx.module_files {
         for item in &file.items {
             match item {
-                HirModuleItem::Func { id, name, args: _, body: _, visibility, ret_ty: _ } => {
+                HirModuleItem::Struct { id, name: name @ ("Vec" | "Option" | "Result"), .. } => {
+                    ctx.error(format!("{} is a built-in type and can not be redefined", name));
+                    poisoned.push(*id);
+                }
+
+                HirModuleItem::TypeAlias { id, name, .. } if matches!(*name, "Vec" | "Option" | "Result") || Primitive::from_name(name).is_some() => {
+                    ctx.error(format!("{} is a built-in type and can not be redefined", name));
+                    poisoned.push(*id);
+                }
+
+                HirModuleItem::Func { id, name: name @ ("Some" | "None" | "Ok" | "Err"), .. } => {
+                    ctx.error(format!("{} is a built-in constructor and can not be redefined", name));
+                    poisoned.push(*id);
+                }
+
+                // a second definition of a name was reported by check_duplicate_definitions, the first one is kept
+                HirModuleItem::Struct { id, name, .. } => {
+                    ctx.module_structs.borrow_mut().declare(name, *id);
+                }
+
+                HirModuleItem::Trait { id, name, .. } => {
+                    ctx.module_traits.borrow_mut().declare(name, *id);
+                }
+
+                HirModuleItem::TypeAlias { id, name, distinct, .. } => {
+                    ctx.module_type_aliases.borrow_mut().declare(name, *id, *distinct);
+                }
+
+                _ => ()
+            }
+        }
+    }
+
+    remove_items(ctx, &poisoned);
+    poisoned.clear();
+
+    import_traits(ctx);
+
+    for file in &ctx.module_files {
+        for item in &file.items {
+            match item {
+                HirModuleItem::Func { id, name, generics, args: _, patterns: _, body: _, visibility, ret_ty: _ } => {
+                    check_bounds(ctx, name, generics);
+
+                    env.define(name, SymbolInfo { 
+                        id: *id, 
+                        kind: SymbolKind::Function 
+                    });
+
+                    if matches!(visibility, HirVisibility::Public) {
+                        ctx.module_exports.push((name, *id));
+                    }
+                },
+
+                HirModuleItem::Const { id, name, visibility, .. } => {
                     env.define(name, SymbolInfo { 
-                        id: id.clone(), 
-                        is_external_name: false 
+                        id: *id, 
+                        kind: SymbolKind::Constant 
                     });
 
                     if matches!(visibility, HirVisibility::Public) {
-                        ctx.module_exports.push((name, id.clone()));
+                        ctx.module_exports.push((name, *id));
                     }
                 },
+
+                HirModuleItem::Struct { .. } | HirModuleItem::Trait { .. } | HirModuleItem::TypeAlias { .. } => (),
+
+                HirModuleItem::Impl { id: impl_id, target, trait_name, methods } => {
+                    if !ctx.module_structs.borrow().contains(target) {
+                        ctx.error(format!("impl for unknown type {}", target));
+                        poisoned.push(*impl_id);
+                        continue;
+                    }
+
+                    if let Some(trait_name) = trait_name {
+                        if !ctx.module_traits.borrow().contains(trait_name) {
+                            ctx.error(format!("impl of unknown trait {} for {}", trait_name, target));
+                            poisoned.push(*impl_id);
+                            continue;
+                        }
+
+                        if !ctx.module_traits.borrow_mut().add_impl(target, trait_name) {
+                            ctx.error(format!("trait {} is implemented more than once for {}", trait_name, target));
+                            poisoned.push(*impl_id);
+                            continue;
+                        }
+                    }
+
+                    // methods live in the namespace of their type, not in the flat module scope
+                    for method in methods {
+                        let HirModuleItem::Func { id, name, generics, args, .. } = method else {
+                            ctx.error("only functions can be defined in impl blocks".to_string());
+                            continue;
+                        };
+
+                        check_bounds(ctx, name, generics);
+
+                        let has_self = args.first().is_some_and(|arg| arg.0 == "self");
+
+                        // a second definition was reported by check_duplicate_definitions
+                        ctx.module_methods.borrow_mut().add_method(target, name, MethodInfo { id: *id, has_self });
+                    }
+                }
             }
         }
     }
+
+    remove_items(ctx, &poisoned);
 }
 
 fn link_local_names<'a>(env: &mut Env<'a>, ctx: &GlobalCtx<'a>, expr: &HirExpr<'a>) {
@@ -77,6 +519,36 @@ fn link_local_names<'a>(env: &mut Env<'a>, ctx: &GlobalCtx<'a>, expr: &HirExpr<'
             link_local_names(env, ctx, ret_expr);
         }
 
+        HirExprKind::Cast { expr, ty } => {
+            link_local_names(env, ctx, expr);
+            check_type_hint(ctx, ty, &env.type_params);
+        }
+
+        HirExprKind::Closure { args, body } => {
+            let names: Vec<(&str, usize)> = args.iter().enumerate()
+                .filter(|(_, arg)| arg.0 != "_")
+                .map(|(pos, arg)| (arg.0, pos + 1))
+                .collect();
+            check_unique_params(ctx, "closure", &names);
+
+            for arg in args {
+                check_type_hint(ctx, &arg.2, &env.type_params);
+            }
+
+            env.enter_closure(expr.id);
+
+            for arg in args {
+                env.define(arg.0, SymbolInfo {
+                    id: arg.1,
+                    kind: SymbolKind::Parameter
+                });
+            }
+
+            link_local_names(env, ctx, body);
+
+            env.leave_closure();
+        }
+
         HirExprKind::If { cond, then, _else } => {
             link_local_names(env, ctx, cond);
             link_local_names(env, ctx, then);
@@ -87,59 +559,363 @@ fn link_local_names<'a>(env: &mut Env<'a>, ctx: &GlobalCtx<'a>, expr: &HirExpr<'
         }
 
         HirExprKind::Id(id) => {
-            if let Some(symbol) = env.lookup(id) {
-                ctx.module_symbols.borrow_mut().add_pair(expr.id, SymbolInfo {
-                    id: symbol.id,
-                    is_external_name: symbol.is_external_name
-                });
+            let Some(symbol) = lookup_local(env, ctx, id) else {
+                ctx.error(unknown_name_message("variable", id, suggest_name(env, ctx, id, false).as_deref()));
+                return;
+            };
+
+            ctx.module_symbols.borrow_mut().add_pair(expr.id, SymbolInfo {
+                id: symbol.id,
+                kind: symbol.kind
+            });
+        }
+
+        // an imported constant gets an id of its own here, carrying the type and value from the interface
+        HirExprKind::Path { alias, name } if env.imports.contains_key(alias) => {
+            let module = &env.imports[alias];
+            let Some(interface) = ctx.module_interfaces.iter().find(|interface| interface.name == *module) else {
+                ctx.error(format!("interface of module {} imported as {} was not found", module, alias));
+                return;
+            };
+
+            let Some(constant) = interface.constant(name) else {
+                let consts = interface.consts.iter().filter(|constant| constant.public);
+                let suggestion = suggest(name, consts.map(|constant| constant.name.as_str()))
+                    .map(|constant| format!("{}::{}", alias, constant));
+                ctx.error(unknown_name_message("constant", &format!("{}::{}", alias, name), suggestion.as_deref()));
+                return;
+            };
+
+            if !constant.public {
+                ctx.error(format!("constant {} is private to module {} and can not be used from {}", name, module, ctx.module_name));
+                return;
             }
+
+            let id = HirId::new();
+            ctx.module_ty_info.borrow_mut().insert_type(id, TypeInfo { ty: constant.ty.clone() });
+            ctx.module_consts.borrow_mut().insert_value(id, constant.value);
+
+            ctx.module_symbols.borrow_mut().add_pair(expr.id, SymbolInfo {
+                id,
+                kind: SymbolKind::Constant
+            });
+        }
+
+        HirExprKind::Path { alias, name } => {
+            ctx.error(format!("{}::{} does not name an imported module", alias, name));
         }
 
-        HirExprKind::VarDef { name, value, ty: _ } => {
+        HirExprKind::VarDef { name, value, ty } => {
             link_local_names(env, ctx, value);
 
+            if let Some(ty) = ty {
+                check_type_hint(ctx, ty, &env.type_params);
+            }
+
             env.define(name, SymbolInfo {
                 id: expr.id,
-                is_external_name: false
+                kind: SymbolKind::Local
             });
         }
 
-        HirExprKind::Call { name, args } => {
+        HirExprKind::Destructure { pattern, value, ty } => {
+            link_local_names(env, ctx, value);
+
+            if let Some(ty) = ty {
+                check_type_hint(ctx, ty, &env.type_params);
+            }
+
+            define_pattern(env, ctx, pattern, SymbolKind::Local);
+        }
+
+        HirExprKind::Call { alias: Some(alias), name, args } if ctx.module_structs.borrow().contains(alias) => {
             for arg in args {
                 link_local_names(env, ctx, arg);
             }
 
-            //temporally, next step - find in imports
-            if let Some(symbol) = env.lookup(name) {
-                ctx.module_symbols.borrow_mut().add_pair(expr.id, SymbolInfo {
-                    id: symbol.id,
-                    is_external_name: symbol.is_external_name 
-                });
+            let Some(method) = ctx.module_methods.borrow().get_method(alias, name) else {
+                let suggestion = suggest(name, ctx.module_methods.borrow().names_of(alias))
+                    .map(|method| format!("{}::{}", alias, method));
+                ctx.error(unknown_name_message("function", &format!("{}::{}", alias, name), suggestion.as_deref()));
+                return;
+            };
+
+            ctx.module_symbols.borrow_mut().add_pair(expr.id, SymbolInfo {
+                id: method.id,
+                kind: SymbolKind::Function
+            });
+        }
+
+        HirExprKind::Call { alias: Some(alias), name, args } if env.imports.contains_key(alias) => {
+            for arg in args {
+                link_local_names(env, ctx, arg);
+            }
+
+            let module = &env.imports[alias];
+            let Some(interface) = ctx.module_interfaces.iter().find(|interface| interface.name == *module) else {
+                ctx.error(format!("interface of module {} imported as {} was not found", module, alias));
+                return;
+            };
+
+            let Some(function) = interface.function(name) else {
+                let functions = interface.functions.iter().filter(|function| function.public);
+                let suggestion = suggest(name, functions.map(|function| function.name.as_str()))
+                    .map(|function| format!("{}::{}", alias, function));
+                ctx.error(unknown_name_message("function", &format!("{}::{}", alias, name), suggestion.as_deref()));
+                return;
+            };
+
+            if !function.public {
+                ctx.error(format!("function {} is private to module {} and can not be called from {}", name, module, ctx.module_name));
+                return;
+            }
+
+            let id = ctx.module_imports.borrow_mut().import(mangle(module, name), function.ty.clone());
+
+            ctx.module_symbols.borrow_mut().add_pair(expr.id, SymbolInfo {
+                id,
+                kind: SymbolKind::Import
+            });
+        }
+
+        HirExprKind::Call { alias: Some(alias), name, args } => {
+            for arg in args {
+                link_local_names(env, ctx, arg);
+            }
+
+            ctx.error(format!("{}::{} does not name an imported module or a type", alias, name));
+        }
+
+        HirExprKind::Call { alias: None, name, args } => {
+            for arg in args {
+                link_local_names(env, ctx, arg);
+            }
+
+            let Some(symbol) = lookup_local(env, ctx, name) else {
+                ctx.error(unknown_name_message("function", name, suggest_name(env, ctx, name, true).as_deref()));
+                return;
+            };
+
+            ctx.module_symbols.borrow_mut().add_pair(expr.id, SymbolInfo {
+                id: symbol.id,
+                kind: symbol.kind 
+            });
+        }
+
+        // the method itself depends on the receiver type and is looked up during typing
+        HirExprKind::MethodCall { receiver, method: _, args } => {
+            link_local_names(env, ctx, receiver);
+
+            for arg in args {
+                link_local_names(env, ctx, arg);
+            }
+        }
+
+        HirExprKind::StructLiteral { name: _, fields } => {
+            for field in fields {
+                link_local_names(env, ctx, &field.1);
             }
         }
 
+        HirExprKind::FieldAccess { expr, field: _ } => {
+            link_local_names(env, ctx, expr);
+        }
+
+        HirExprKind::Tuple(elems) | HirExprKind::Array(elems) => {
+            for elem in elems {
+                link_local_names(env, ctx, elem);
+            }
+        }
+
+        HirExprKind::Index { expr, index } => {
+            link_local_names(env, ctx, expr);
+            link_local_names(env, ctx, index);
+        }
+
+        HirExprKind::For { pattern, iterable, body } => {
+            link_local_names(env, ctx, iterable);
+
+            env.push_scope();
+            define_pattern(env, ctx, pattern, SymbolKind::Local);
+            link_local_names(env, ctx, body);
+            env.pop_scope();
+        }
+
+        HirExprKind::TupleIndex { expr, index: _ } | HirExprKind::Try(expr) | HirExprKind::Defer(expr) => {
+            link_local_names(env, ctx, expr);
+        }
+
+        HirExprKind::Variant { variant: _, value: Some(value) } => {
+            link_local_names(env, ctx, value);
+        }
+
         _ => ()
     }
 }
 
 
+fn resolve_function<'a>(env: &mut Env<'a>, ctx: &GlobalCtx<'a>, item: &HirModuleItem<'a>) {
+    let HirModuleItem::Func { name, generics, args, patterns, body, ret_ty, .. } = item else {
+        return;
+    };
+
+    env.type_params = generics.iter().map(|generic| generic.name).collect();
+
+    for arg in args {
+        check_type_hint(ctx, &arg.2, &env.type_params);
+    }
+
+    if let Some(ret_ty) = ret_ty {
+        check_type_hint(ctx, ret_ty, &env.type_params);
+    }
+
+    let mut names: Vec<(&str, usize)> = args.iter().enumerate()
+        .filter(|(_, arg)| arg.0 != "_")
+        .map(|(pos, arg)| (arg.0, pos + 1))
+        .collect();
+
+    for (pos, pattern) in patterns {
+        let mut bindings = Vec::new();
+        collect_bindings(pattern, &mut bindings);
+
+        // names repeated inside one pattern are reported when the pattern is defined
+        for (index, binding) in bindings.iter().enumerate() {
+            if !bindings[..index].iter().any(|earlier| earlier.0 == binding.0) {
+                names.push((binding.0, pos + 1));
+            }
+        }
+    }
+
+    names.sort_by_key(|name| name.1);
+    check_unique_params(ctx, name, &names);
+
+    env.push_scope();
+
+    for arg in args.iter().filter(|arg| arg.0 != "_") {
+        env.define(arg.0, SymbolInfo { 
+            id: arg.1, 
+            kind: SymbolKind::Parameter 
+        });
+    }
+
+    for (_, pattern) in patterns {
+        define_pattern(env, ctx, pattern, SymbolKind::Parameter);
+    }
+
+    link_local_names(env, ctx, body);
+
+    env.pop_scope();
+}
+
+fn collect_bindings<'a>(pattern: &HirPattern<'a>, bindings: &mut Vec<(&'a str, HirId)>) {
+    match pattern {
+        HirPattern::Binding(name, id) => bindings.push((name, *id)),
+        HirPattern::Wildcard => (),
+        HirPattern::Tuple(elems) => {
+            for elem in elems {
+                collect_bindings(elem, bindings);
+            }
+        }
+        HirPattern::Struct { name: _, fields } => {
+            for (_, field) in fields {
+                collect_bindings(field, bindings);
+            }
+        }
+    }
+}
+
+// every name of a pattern is a separate local or parameter, bound to the id of its binding
+fn define_pattern<'a>(env: &mut Env<'a>, ctx: &GlobalCtx, pattern: &HirPattern<'a>, kind: SymbolKind) {
+    let mut bindings = Vec::new();
+    collect_bindings(pattern, &mut bindings);
+
+    for (index, (name, id)) in bindings.iter().copied().enumerate() {
+        // the first binding of a name is kept, later ones stay unresolved
+        if bindings[..index].iter().any(|binding| binding.0 == name) {
+            ctx.error(format!("name {} is bound more than once in the same pattern", name));
+            continue;
+        }
+
+        env.define(name, SymbolInfo {
+            id,
+            kind
+        });
+    }
+}
+
+// interfaces are owned by the context, so the names taken from them are leaked to live as long as the sources
+fn leak(name: &str) -> &'static str {
+    Box::leak(name.to_string().into_boxed_str())
+}
+
+// pub traits of imported modules can be implemented and used as bounds under their own name, their impls are
+// kept under the qualified name of the type, which is what the type will be called once types can be imported
+fn import_traits(ctx: &GlobalCtx) {
+    for interface in &ctx.module_interfaces {
+        for module_trait in &interface.traits {
+            let name = leak(&module_trait.name);
+
+            if !ctx.module_traits.borrow_mut().declare(name, HirId::new()) {
+                ctx.error(format!("trait {} of module {} conflicts with another trait named {}", name, interface.name, name));
+                continue;
+            }
+
+            let methods = module_trait.methods.iter()
+                .map(|(method, ty)| TraitMethodInfo { name: leak(method), ty: ty.clone() })
+                .collect();
+
+            ctx.module_traits.borrow_mut().set_methods(name, methods);
+        }
+
+        for (target, trait_name) in &interface.impls {
+            if ctx.module_traits.borrow().contains(trait_name) {
+                ctx.module_traits.borrow_mut().add_impl(leak(&mangle(&interface.name, target)), leak(trait_name));
+            }
+        }
+    }
+}
+
 fn try_to_resolve_locals<'a>(env: &mut Env<'a>, ctx: &GlobalCtx<'a>) {
     for file in &ctx.module_files {
+        // system modules do not provide interfaces yet, so calls into them stay unresolved
+        env.imports = file.imports.iter()
+            .filter(|import| !import.system)
+            .map(|import| (import.alias.unwrap_or(import.path.last().unwrap()), import.path.join(".")))
+            .collect();
+
         for item in &file.items {
             match item {
-                HirModuleItem::Func { name: _, id, args, body, .. } => {
-                    env.push_scope();
-
-                    for arg in args {
-                        env.define(arg.0, SymbolInfo { 
-                            id: id.clone(), 
-                            is_external_name: false 
-                        });
+                HirModuleItem::Func { .. } => resolve_function(env, ctx, item),
+
+                HirModuleItem::Const { ty, value, .. } => {
+                    check_type_hint(ctx, ty, &[]);
+                    link_local_names(env, ctx, value);
+                }
+
+                HirModuleItem::Struct { fields, .. } => {
+                    for (_, ty) in fields {
+                        check_type_hint(ctx, ty, &[]);
+                    }
+                }
+
+                HirModuleItem::Trait { methods, .. } => {
+                    for method in methods {
+                        for (_, ty) in &method.args {
+                            check_type_hint(ctx, ty, &[]);
+                        }
+
+                        if let Some(ret_ty) = &method.ret_ty {
+                            check_type_hint(ctx, ret_ty, &[]);
+                        }
                     }
+                }
 
-                    link_local_names(env, ctx,body);
+                HirModuleItem::TypeAlias { ty, .. } => check_type_hint(ctx, ty, &[]),
 
-                    env.pop_scope();
+                HirModuleItem::Impl { methods, .. } => {
+                    for method in methods {
+                        resolve_function(env, ctx, method);
+                    }
                 }
             }
         }
@@ -151,5 +927,18 @@ pub fn resolve_module<'a>(ctx: &mut GlobalCtx<'a>) {
 
     track_global_names(&mut env, ctx);
 
+    check_alias_cycles(ctx);
+
     try_to_resolve_locals(&mut env, ctx);
-}
\ No newline at end of file
+
+    // nothing imports an executable, so its pub items besides `main` are only reachable from inside
+    if matches!(ctx.build_type, BuildType::Executable) {
+        for (name, id) in &ctx.module_exports {
+            if *name != "main" && !ctx.module_symbols.borrow().is_referenced(id) {
+                ctx.warn(format!("{} is pub but never used, executables only export main", name));
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod tests;
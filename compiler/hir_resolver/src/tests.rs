@@ -0,0 +1,205 @@
+use ast_lowering::translate_to_hir;
+use middle::{BuildType, GlobalCtx};
+use parser::parse_file;
+
+use crate::resolve_module;
+
+// the errors reported while lowering and resolving the source
+fn resolve_source(source: &str) -> Vec<String> {
+    resolve_files(&[("test", source)])
+}
+
+fn resolve_files(sources: &[(&str, &str)]) -> Vec<String> {
+    let parsed: Vec<_> = sources.iter().map(|(name, source)| parse_file(name, source)).collect();
+
+    let mut ctx = GlobalCtx::new("test".to_string(), "x86_64".to_string(), BuildType::Executable);
+
+    for file in &parsed {
+        let file = translate_to_hir(&ctx, file);
+        ctx.module_files.push(file);
+    }
+
+    resolve_module(&mut ctx);
+
+    ctx.module_errors.take()
+}
+
+#[test]
+fn reports_malformed_built_in_calls() {
+    assert_eq!(
+        resolve_source("fn main() -> Int { val v: Vec<Int> = Vec::new(1); ret 0; }"),
+        vec!["Vec::new takes no arguments but 1 were given"]
+    );
+    assert_eq!(
+        resolve_source("fn main() -> Int { val o = Some(1, 2); ret 0; }"),
+        vec!["Some takes exactly one argument but 2 were given"]
+    );
+}
+
+#[test]
+fn reports_misplaced_self() {
+    assert_eq!(
+        resolve_source("fn f(x: Self) -> Int { ret 0; }"),
+        vec!["Self is only available inside impl blocks and traits, but used in f"]
+    );
+    assert_eq!(
+        resolve_source("struct P { x: Int } impl P { fn f(a: Int, self) -> Int { ret a; } }"),
+        vec!["self must be the first parameter of f"]
+    );
+}
+
+#[test]
+fn reports_redefined_built_ins() {
+    assert_eq!(
+        resolve_source("struct Vec { a: Int } fn Some(x: Int) -> Int { ret x; }"),
+        vec!["Vec is a built-in type and can not be redefined", "Some is a built-in constructor and can not be redefined"]
+    );
+}
+
+#[test]
+fn reports_invalid_impls_once() {
+    let source = "
+        struct P { x: Int }
+        trait Show {
+            fn show(self) -> Int;
+        }
+        impl Ghost { fn f(self) -> Int { ret 1; } }
+        impl Nope for P { fn g(self) -> Int { ret 1; } }
+        impl Show for P { fn show(self) -> Int { ret 1; } }
+        impl Show for P { fn other(self) -> Int { ret 2; } }
+    ";
+
+    assert_eq!(resolve_source(source), vec![
+        "impl for unknown type Ghost",
+        "impl of unknown trait Nope for P",
+        "trait Show is implemented more than once for P"
+    ]);
+}
+
+#[test]
+fn reports_unknown_bounds() {
+    assert_eq!(
+        resolve_source("fn f<T: Missing>(x: T) -> T { ret x; }"),
+        vec!["unknown trait Missing in bounds of T in f"]
+    );
+}
+
+#[test]
+fn reports_each_alias_cycle_once() {
+    assert_eq!(
+        resolve_source("type A = B; type B = (Int, A); fn f(a: A) -> Int { ret 0; }"),
+        vec!["type A refers to itself: A -> B -> A"]
+    );
+}
+
+#[test]
+fn reports_names_bound_twice_in_a_pattern() {
+    assert_eq!(
+        resolve_source("fn f((a, a): (Int, Int)) -> Int { ret a; }"),
+        vec!["name a is bound more than once in the same pattern"]
+    );
+    assert_eq!(
+        resolve_source("fn f() -> Int { val (b, b) = (1, 2); ret b; }"),
+        vec!["name b is bound more than once in the same pattern"]
+    );
+}
+
+#[test]
+fn reports_both_positions_of_repeated_params() {
+    assert_eq!(
+        resolve_source("fn f(a: Int, b: Int, a: Int) -> Int { ret b; }"),
+        vec!["parameter a of f is defined more than once, at positions 1 and 3"]
+    );
+    assert_eq!(
+        resolve_source("fn f((a, b): (Int, Int), a: Int) -> Int { ret b; }"),
+        vec!["parameter a of f is defined more than once, at positions 1 and 2"]
+    );
+    assert_eq!(
+        resolve_source("fn f() -> Int { val g = |x: Int, x: Int| x; ret 0; }"),
+        vec!["parameter x of closure is defined more than once, at positions 1 and 2"]
+    );
+}
+
+#[test]
+fn suggests_names_of_the_same_kind() {
+    let source = "
+        fn compute(a: Int) -> Int { ret a; }
+        pub fn main() -> Int {
+            val total = 1;
+            val count = 2;
+            cmopute(conut);
+            computr;
+            totl();
+            ret 0;
+        }
+    ";
+
+    assert_eq!(resolve_source(source), vec![
+        "unknown variable conut, did you mean count?",
+        "unknown function cmopute, did you mean compute?",
+        "unknown variable computr",
+        "unknown function totl"
+    ]);
+}
+
+#[test]
+fn does_not_suggest_for_short_names() {
+    assert_eq!(
+        resolve_source("fn main() -> Int { val a = 1; ret b; }"),
+        vec!["unknown variable b"]
+    );
+}
+
+#[test]
+fn reports_definitions_repeated_across_files() {
+    let first = "
+        fn area(x: Int) -> Int { ret x; }
+        struct Shape { x: Int }
+    ";
+    let second = "
+        const area: Int = 1;
+        trait Shape {
+            fn x(self) -> Int;
+        }
+        fn main() -> Int { ret 0; }
+    ";
+
+    assert_eq!(resolve_files(&[("first", first), ("second", second)]), vec![
+        "area is defined more than once: as function at first, item 1 and as constant at second, item 1",
+        "Shape is defined more than once: as struct at first, item 2 and as trait at second, item 2"
+    ]);
+}
+
+#[test]
+fn keeps_values_and_types_apart() {
+    let source = "
+        struct point { x: Int }
+        fn point(x: Int) -> Int { ret x; }
+        fn main() -> Int { ret point(1); }
+    ";
+
+    assert_eq!(resolve_source(source), Vec::<String>::new());
+}
+
+#[test]
+fn reports_methods_repeated_in_impls_of_one_type() {
+    let source = "
+        struct P { x: Int }
+        impl P { fn get(self) -> Int { ret self.x; } }
+        impl P { fn set(self) -> Int { ret 0; } fn get(self) -> Int { ret 1; } }
+    ";
+
+    assert_eq!(resolve_source(source), vec!["method P::get is defined more than once: at test, item 2, method 1 and at test, item 3, method 2"]);
+}
+
+#[test]
+fn scopes_imports_to_their_file() {
+    assert_eq!(
+        resolve_files(&[("first", "import util\nfn main() -> Int { ret 0; }"), ("second", "import util\nfn f() -> Int { ret 0; }")]),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        resolve_source("import util\nimport tools.util\nfn main() -> Int { ret 0; }"),
+        vec!["util is imported more than once in test: as module util and as module tools.util"]
+    );
+}
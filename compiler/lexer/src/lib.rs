@@ -1,6 +1,8 @@
+use std::num::IntErrorKind;
+
 use tokens::keywords::RESERVED_KEYWORDS;
 use tokens::Token;
-use tokens::Token::{IDENTIFIER, INTEGER, FLOAT};
+use tokens::Token::{IDENTIFIER, INTEGER, FLOAT, TYPEDINTEGER, TYPEDFLOAT};
 use crate::cursor::Cursor;
 use crate::DigitBase::DECIMAL;
 
@@ -8,6 +10,12 @@ pub mod cursor;
 
 pub type LexerResult<Tok, Loc, Err> = Result<(Loc, Tok, Loc), Err>;
 
+const NUMBER_SUFFIXES: [&str; 12] = [
+    "i8", "i16", "i32", "i64", "i128",
+    "u8", "u16", "u32", "u64", "u128",
+    "f32", "f64"
+];
+
 #[derive(Eq, PartialEq)]
 enum DigitBase {
     HEX,
@@ -18,6 +26,7 @@ enum DigitBase {
 
 pub struct Lexer<'input> {
     cursor: Cursor<'input>,
+    after_dot: bool,
 }
 
 impl<'input> Lexer<'input> {
@@ -25,9 +34,10 @@ impl<'input> Lexer<'input> {
     pub fn new(input: &'input str) -> Lexer<'input> {
         Self {
             cursor: Cursor::new(input),
+            after_dot: false,
         }
     }
-    fn process_number(&mut self, first: char, neg: bool) -> LexerResult<Token<'input>, usize, &'static str> {
+    fn process_number(&mut self, first: char) -> LexerResult<Token<'input>, usize, &'static str> {
         // select number base
         let mut base = DECIMAL;
 
@@ -40,6 +50,8 @@ impl<'input> Lexer<'input> {
                 'o' => DigitBase::OCTAL,
                 '0'..='9' | '_' => DECIMAL,
                 '.' => {real_flag = true; DECIMAL},
+                // a suffix, known or not
+                c if c.is_ascii_alphabetic() => DECIMAL,
 
                 //just a zero
                 _ => return Ok((self.cursor.column(), INTEGER(0), self.cursor.line()))
@@ -54,10 +66,6 @@ impl<'input> Lexer<'input> {
         // process number
         let mut result_number = String::new();
 
-        if neg {
-            result_number.push('-');
-        }
-
         result_number.push(first);
 
         loop {
@@ -71,6 +79,7 @@ impl<'input> Lexer<'input> {
                 }
                 '_' => { self.cursor.bump(); },
                 '0'..='9' => result_number.push(self.cursor.bump().unwrap()),
+                'f' if base != DigitBase::HEX => break,
                 'a'..='f' | 'A'..='F' => {
                     if base == DigitBase::HEX  {
                         result_number.push(self.cursor.bump().unwrap())
@@ -82,14 +91,29 @@ impl<'input> Lexer<'input> {
             }
         }
 
-        if real_flag {
+        let suffix = self.process_number_suffix()?;
+
+        let float_suffix = matches!(suffix, Some(s) if s.starts_with('f'));
+
+        if real_flag || float_suffix {
             if base != DECIMAL {
                 return Err("Invalid number format parsed");
             }
 
+            if real_flag && suffix.is_some() && !float_suffix {
+                return Err("Integer suffix on floating point number");
+            }
+
+            let value = result_number.parse::<f64>().unwrap();
+
+            let token = match suffix {
+                Some(suffix) => TYPEDFLOAT(value, suffix),
+                None => FLOAT(value)
+            };
+
             return Ok((
                 self.cursor.column(),
-                FLOAT(result_number.parse::<f64>().unwrap()),
+                token,
                 self.cursor.line()
             ));
         }
@@ -97,23 +121,62 @@ impl<'input> Lexer<'input> {
         //convert to integer
 
         let result = match base {
-            DECIMAL => i64::from_str_radix(result_number.as_str(), 10),
-            DigitBase::HEX => i64::from_str_radix(result_number.as_str(), 16),
-            DigitBase::OCTAL => i64::from_str_radix(result_number.as_str(), 8),
-            DigitBase::BINARY => i64::from_str_radix(result_number.as_str(), 2),
+            DECIMAL => u128::from_str_radix(result_number.as_str(), 10),
+            DigitBase::HEX => u128::from_str_radix(result_number.as_str(), 16),
+            DigitBase::OCTAL => u128::from_str_radix(result_number.as_str(), 8),
+            DigitBase::BINARY => u128::from_str_radix(result_number.as_str(), 2),
         };
 
-        if result.is_err() {
-            return Err("Invalid number format parsed");
-        }
+        // the range of the literal's type is checked once its type is known
+        let value = match result {
+            Ok(value) => value,
+            Err(err) if *err.kind() == IntErrorKind::PosOverflow => return Err("Integer literal does not fit in 128 bits"),
+            Err(_) => return Err("Invalid number format parsed")
+        };
+
+        let token = match suffix {
+            Some(suffix) => TYPEDINTEGER(value, suffix),
+            None => INTEGER(value)
+        };
 
         Ok((
             self.cursor.column(),
-            INTEGER(result.unwrap()),
+            token,
             self.cursor.line()
         ))
     }
 
+    // digits after a dot are a tuple index, so `t.0.1` is not lexed as `t` `.` `0.1`
+    fn process_tuple_index(&mut self, first: char) -> LexerResult<Token<'input>, usize, &'static str> {
+        let mut index = String::from(first);
+
+        while self.cursor.first().is_ascii_digit() {
+            index.push(self.cursor.bump().unwrap());
+        }
+
+        match index.parse::<u128>() {
+            Ok(value) => Ok((self.cursor.column(), INTEGER(value), self.cursor.line())),
+            Err(_) => Err("Invalid tuple index")
+        }
+    }
+
+    fn process_number_suffix(&mut self) -> Result<Option<&'static str>, &'static str> {
+        if !self.cursor.first().is_ascii_alphabetic() {
+            return Ok(None);
+        }
+
+        let mut suffix = String::new();
+
+        while self.cursor.first().is_ascii_alphanumeric() {
+            suffix.push(self.cursor.bump().unwrap());
+        }
+
+        match NUMBER_SUFFIXES.iter().find(|known| **known == suffix) {
+            Some(known) => Ok(Some(known)),
+            None => Err("Unknown literal suffix")
+        }
+    }
+
     fn process_id(&mut self, first: char) -> LexerResult<Token<'input>, usize, &'static str> {
         let mut result = String::from(first);
 
@@ -201,6 +264,8 @@ impl<'input> Iterator for Lexer<'input> {
             }
         }
         
+        let after_dot = std::mem::take(&mut self.after_dot);
+
         let tok_type = match first {
             '+' => Token::PLUS,
             '/' => Token::SLASH,
@@ -209,9 +274,23 @@ impl<'input> Iterator for Lexer<'input> {
             ')' => Token::RBRACKET,
             '{' => Token::LRBRACKET,
             '}' => Token::RRBRACKET,
+            '[' => Token::LSBRACKET,
+            ']' => Token::RSBRACKET,
+            '?' => Token::QUESTION,
             ';' => Token::SEMICOLON,
-            '>' => Token::GT,
-            '<' => Token::LT,
+            '>' => {
+                match self.cursor.peek() {
+                    // the second `>` is left for the next token, so that `>>` can close nested type arguments
+                    '>' => Token::JOINTGT,
+                    _ => Token::GT
+                }
+            }
+            '<' => {
+                match self.cursor.peek() {
+                    '<' => {self.cursor.bump(); Token::SHL}
+                    _ => Token::LT
+                }
+            }
             '"' => {
                 return Some(self.process_string_literal());
             }
@@ -223,11 +302,15 @@ impl<'input> Iterator for Lexer<'input> {
             }
             ',' => Token::COMMA,
             ':' => Token::COLON,
+            '.' => {
+                self.after_dot = true;
+                Token::DOT
+            }
             '!' => Token::EXCLAMATION,
             '|' => {
                 match self.cursor.peek() {
                     '|' => {self.cursor.bump(); Token::OR},
-                    _ => panic!()
+                    _ => Token::PIPE
                 }
             },
             '&' => {
@@ -236,18 +319,17 @@ impl<'input> Iterator for Lexer<'input> {
                     c => panic!("{}", c)
                 }
             },
-            '-' => {
-                match self.cursor.first() {
-                    c @ '0'..='9' => {self.cursor.bump(); return Some(self.process_number(c, true))},
-                    _ => Token::MINUS,
-                }
-            }
+            '-' => Token::MINUS,
 
             c if c == '_' || unicode_xid::UnicodeXID::is_xid_start(c) => return Some(self.process_id(c)),
-            c @ '0'..='9' => return Some(self.process_number(c, false)),
+            c @ '0'..='9' if after_dot => return Some(self.process_tuple_index(c)),
+            c @ '0'..='9' => return Some(self.process_number(c)),
             _ => return Some(Err("Unknown symbol"))
         };
 
         Some(Ok((self.cursor.column(), tok_type, self.cursor.line())))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests;
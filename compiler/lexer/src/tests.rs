@@ -0,0 +1,50 @@
+use tokens::Token;
+
+use crate::Lexer;
+
+fn tokens(source: &str) -> Vec<Token<'_>> {
+    Lexer::new(source).map(|token| token.unwrap().1).collect()
+}
+
+#[test]
+fn lexes_literal_suffixes() {
+    assert_eq!(tokens("10u8 7i128 0xffu8 1.5f32"), [
+        Token::TYPEDINTEGER(10, "u8"),
+        Token::TYPEDINTEGER(7, "i128"),
+        Token::TYPEDINTEGER(255, "u8"),
+        Token::TYPEDFLOAT(1.5, "f32")
+    ]);
+}
+
+#[test]
+fn lexes_literals_without_suffix_as_untyped() {
+    assert_eq!(tokens("3 2.0"), [Token::INTEGER(3), Token::FLOAT(2.0)]);
+}
+
+#[test]
+fn lexes_double_greater_than_as_joint_and_plain() {
+    assert_eq!(tokens("a >> b"), [Token::IDENTIFIER("a"), Token::JOINTGT, Token::GT, Token::IDENTIFIER("b")]);
+    assert_eq!(tokens("a > > b"), [Token::IDENTIFIER("a"), Token::GT, Token::GT, Token::IDENTIFIER("b")]);
+    assert_eq!(tokens(">>>"), [Token::JOINTGT, Token::JOINTGT, Token::GT]);
+}
+
+#[test]
+fn lexes_nested_tuple_index_as_separate_integers() {
+    let expected = [Token::IDENTIFIER("t"), Token::DOT, Token::INTEGER(0), Token::DOT, Token::INTEGER(1)];
+
+    assert_eq!(tokens("t.0.1"), expected);
+    assert_eq!(tokens("t.0 .1"), expected);
+}
+
+#[test]
+fn lexes_integers_up_to_128_bits_without_their_sign() {
+    assert_eq!(tokens("-340282366920938463463374607431768211455"), [Token::MINUS, Token::INTEGER(u128::MAX)]);
+    assert_eq!(tokens("a-1"), [Token::IDENTIFIER("a"), Token::MINUS, Token::INTEGER(1)]);
+}
+
+#[test]
+fn reports_unknown_literal_suffixes() {
+    assert_eq!(Lexer::new("1q8").next().unwrap().unwrap_err(), "Unknown literal suffix");
+    assert_eq!(Lexer::new("0q8").next().unwrap().unwrap_err(), "Unknown literal suffix");
+    assert_eq!(Lexer::new("340282366920938463463374607431768211456").next().unwrap().unwrap_err(), "Integer literal does not fit in 128 bits");
+}
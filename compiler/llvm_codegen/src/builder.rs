@@ -1,13 +1,13 @@
 use hir::HirBinOps;
-use inkwell::{builder::Builder, values::{BasicValue, BasicValueEnum}, FloatPredicate, IntPredicate};
+use inkwell::{builder::Builder, types::BasicTypeEnum, values::{BasicValue, BasicValueEnum}, FloatPredicate, IntPredicate};
 use middle::ty::{LangType, Primitive};
 
 fn build_sum<'llvm>(builder: &Builder<'llvm>, _type: &LangType, lhs: BasicValueEnum<'llvm>, rhs: BasicValueEnum<'llvm>) -> BasicValueEnum<'llvm> {
     match _type {
-        LangType::Primitives(Primitive::Int)| LangType::Primitives(Primitive::Char) => 
+        LangType::Primitives(p) if p.is_integer() => 
             builder.build_int_add(lhs.into_int_value(), rhs.into_int_value(), "int_add")
             .unwrap().as_basic_value_enum(),
-        LangType::Primitives(Primitive::Float) => 
+        LangType::Primitives(p) if p.is_float() => 
             builder.build_float_add(lhs.into_float_value(), rhs.into_float_value(), "float_add")
             .unwrap().as_basic_value_enum(),
         _ => panic!("{:?}", _type)
@@ -16,10 +16,10 @@ fn build_sum<'llvm>(builder: &Builder<'llvm>, _type: &LangType, lhs: BasicValueE
 
 fn build_sub<'llvm>(builder: &Builder<'llvm>, _type: &LangType, lhs: BasicValueEnum<'llvm>, rhs: BasicValueEnum<'llvm>) -> BasicValueEnum<'llvm> {
     match _type {
-        LangType::Primitives(Primitive::Int)| LangType::Primitives(Primitive::Char) => 
+        LangType::Primitives(p) if p.is_integer() => 
             builder.build_int_sub(lhs.into_int_value(), rhs.into_int_value(), "int_sub")
             .unwrap().as_basic_value_enum(),
-        LangType::Primitives(Primitive::Float) => 
+        LangType::Primitives(p) if p.is_float() => 
             builder.build_float_sub(lhs.into_float_value(), rhs.into_float_value(), "float_sub")
             .unwrap().as_basic_value_enum(),
         _ => panic!("{:?}", _type)
@@ -28,10 +28,15 @@ fn build_sub<'llvm>(builder: &Builder<'llvm>, _type: &LangType, lhs: BasicValueE
 
 fn build_div<'llvm>(builder: &Builder<'llvm>, _type: &LangType, lhs: BasicValueEnum<'llvm>, rhs: BasicValueEnum<'llvm>) -> BasicValueEnum<'llvm> {
     match _type {
-        LangType::Primitives(Primitive::Int)| LangType::Primitives(Primitive::Char) => 
-            builder.build_int_signed_div(lhs.into_int_value(), rhs.into_int_value(), "int_div")
-            .unwrap().as_basic_value_enum(),
-        LangType::Primitives(Primitive::Float) => 
+        LangType::Primitives(p) if p.is_integer() => 
+            if p.is_unsigned() {
+                builder.build_int_unsigned_div(lhs.into_int_value(), rhs.into_int_value(), "uint_div")
+                .unwrap().as_basic_value_enum()
+            } else {
+                builder.build_int_signed_div(lhs.into_int_value(), rhs.into_int_value(), "int_div")
+                .unwrap().as_basic_value_enum()
+            },
+        LangType::Primitives(p) if p.is_float() => 
             builder.build_float_div(lhs.into_float_value(), rhs.into_float_value(), "float_div")
             .unwrap().as_basic_value_enum(),
         _ => panic!("")
@@ -40,40 +45,63 @@ fn build_div<'llvm>(builder: &Builder<'llvm>, _type: &LangType, lhs: BasicValueE
 
 fn build_mul<'llvm>(builder: &Builder<'llvm>, _type: &LangType, lhs: BasicValueEnum<'llvm>, rhs: BasicValueEnum<'llvm>) -> BasicValueEnum<'llvm> {
     match _type {
-        LangType::Primitives(Primitive::Int)| LangType::Primitives(Primitive::Char) => 
+        LangType::Primitives(p) if p.is_integer() => 
             builder.build_int_mul(lhs.into_int_value(), rhs.into_int_value(), "int_mul")
             .unwrap().as_basic_value_enum(),
-        LangType::Primitives(Primitive::Float) => 
+        LangType::Primitives(p) if p.is_float() => 
             builder.build_float_mul(lhs.into_float_value(), rhs.into_float_value(), "float_mul")
             .unwrap().as_basic_value_enum(),
         _ => panic!("{:?}", _type)
     }
 }
 
+fn int_predicate(primitive: &Primitive, signed: IntPredicate, unsigned: IntPredicate) -> IntPredicate {
+    if primitive.is_unsigned() { unsigned } else { signed }
+}
+
 fn build_gt_compare<'llvm>(builder: &Builder<'llvm>, _type: &LangType, lhs: BasicValueEnum<'llvm>, rhs: BasicValueEnum<'llvm>) -> BasicValueEnum<'llvm> {
     match _type {
-        LangType::Primitives(Primitive::Int)| LangType::Primitives(Primitive::Char) => builder.build_int_compare(IntPredicate::SGT, lhs.into_int_value(), rhs.into_int_value(), "cmpres").unwrap().as_basic_value_enum(),
-        LangType::Primitives(Primitive::Float) => builder.build_float_compare(FloatPredicate::OGT, lhs.into_float_value(), rhs.into_float_value(), "cmpres").unwrap().as_basic_value_enum(),
+        LangType::Primitives(p) if p.is_integer() => builder.build_int_compare(int_predicate(p, IntPredicate::SGT, IntPredicate::UGT), lhs.into_int_value(), rhs.into_int_value(), "cmpres").unwrap().as_basic_value_enum(),
+        LangType::Primitives(p) if p.is_float() => builder.build_float_compare(FloatPredicate::OGT, lhs.into_float_value(), rhs.into_float_value(), "cmpres").unwrap().as_basic_value_enum(),
         _ => panic!("{:?}", _type)
     }
 }
 
 fn build_lt_compare<'llvm>(builder: &Builder<'llvm>, _type: &LangType, lhs: BasicValueEnum<'llvm>, rhs: BasicValueEnum<'llvm>) -> BasicValueEnum<'llvm> {
     match _type {
-        LangType::Primitives(Primitive::Int)| LangType::Primitives(Primitive::Char) => builder.build_int_compare(IntPredicate::SLT, lhs.into_int_value(), rhs.into_int_value(), "cmpres").unwrap().as_basic_value_enum(),
-        LangType::Primitives(Primitive::Float) => builder.build_float_compare(FloatPredicate::OLT, lhs.into_float_value(), rhs.into_float_value(), "cmpres").unwrap().as_basic_value_enum(),
+        LangType::Primitives(p) if p.is_integer() => builder.build_int_compare(int_predicate(p, IntPredicate::SLT, IntPredicate::ULT), lhs.into_int_value(), rhs.into_int_value(), "cmpres").unwrap().as_basic_value_enum(),
+        LangType::Primitives(p) if p.is_float() => builder.build_float_compare(FloatPredicate::OLT, lhs.into_float_value(), rhs.into_float_value(), "cmpres").unwrap().as_basic_value_enum(),
         _ => panic!("{:?}", _type)
     }
 }
 
 fn build_eq_compare<'llvm>(builder: &Builder<'llvm>, _type: &LangType, lhs: BasicValueEnum<'llvm>, rhs: BasicValueEnum<'llvm>) -> BasicValueEnum<'llvm> {
     match _type {
-        LangType::Primitives(Primitive::Int)| LangType::Primitives(Primitive::Char) | LangType::Primitives(Primitive::Bool) => builder.build_int_compare(IntPredicate::EQ, lhs.into_int_value(), rhs.into_int_value(), "cmpres").unwrap().as_basic_value_enum(),
-        LangType::Primitives(Primitive::Float) => builder.build_float_compare(FloatPredicate::UEQ, lhs.into_float_value(), rhs.into_float_value(), "cmpres").unwrap().as_basic_value_enum(),
+        LangType::Primitives(p) if p.is_integer() || *p == Primitive::Bool => builder.build_int_compare(IntPredicate::EQ, lhs.into_int_value(), rhs.into_int_value(), "cmpres").unwrap().as_basic_value_enum(),
+        LangType::Primitives(p) if p.is_float() => builder.build_float_compare(FloatPredicate::UEQ, lhs.into_float_value(), rhs.into_float_value(), "cmpres").unwrap().as_basic_value_enum(),
         _ => panic!("{:?}", _type)
     }
 }
 
+fn build_shl<'llvm>(builder: &Builder<'llvm>, _type: &LangType, lhs: BasicValueEnum<'llvm>, rhs: BasicValueEnum<'llvm>) -> BasicValueEnum<'llvm> {
+    match _type {
+        LangType::Primitives(p) if p.is_integer() => 
+            builder.build_left_shift(lhs.into_int_value(), rhs.into_int_value(), "shl")
+            .unwrap().as_basic_value_enum(),
+        _ => panic!("Invalid type to process [shl] operation: {:?}", _type)
+    }
+}
+
+fn build_shr<'llvm>(builder: &Builder<'llvm>, _type: &LangType, lhs: BasicValueEnum<'llvm>, rhs: BasicValueEnum<'llvm>) -> BasicValueEnum<'llvm> {
+    match _type {
+        // arithmetic shift keeps the sign of signed operands, logical shift fills unsigned ones with zeros
+        LangType::Primitives(p) if p.is_integer() => 
+            builder.build_right_shift(lhs.into_int_value(), rhs.into_int_value(), !p.is_unsigned(), "shr")
+            .unwrap().as_basic_value_enum(),
+        _ => panic!("Invalid type to process [shr] operation: {:?}", _type)
+    }
+}
+
 fn build_and<'llvm>(builder: &Builder<'llvm>, _type: &LangType, lhs: BasicValueEnum<'llvm>, rhs: BasicValueEnum<'llvm>) -> BasicValueEnum<'llvm> {
     match _type {
         LangType::Primitives(Primitive::Bool) => builder.build_and(lhs.into_int_value(), rhs.into_int_value(), "ssl_and").unwrap().as_basic_value_enum(),
@@ -104,6 +132,78 @@ pub(crate) fn build_llvm_binop<'llvm>(
         HirBinOps::OR => build_or(builder, binary_ops_type, lhs, rhs),
         HirBinOps::GT => build_gt_compare(builder, binary_ops_type, lhs, rhs),
         HirBinOps::LT => build_lt_compare(builder, binary_ops_type, lhs, rhs),
-        HirBinOps::EQ => build_eq_compare(builder, binary_ops_type, lhs, rhs)
+        HirBinOps::EQ => build_eq_compare(builder, binary_ops_type, lhs, rhs),
+        HirBinOps::SHL => build_shl(builder, binary_ops_type, lhs, rhs),
+        HirBinOps::SHR => build_shr(builder, binary_ops_type, lhs, rhs)
+    }
+}
+fn is_zero_extended(primitive: &Primitive) -> bool {
+    primitive.is_unsigned() || matches!(primitive, Primitive::Bool | Primitive::Char)
+}
+
+pub(crate) fn build_llvm_cast<'llvm>(
+    builder: &Builder<'llvm>,
+    value: BasicValueEnum<'llvm>,
+    from: &LangType,
+    to: &LangType,
+    target_llvm_ty: BasicTypeEnum<'llvm>
+) -> BasicValueEnum<'llvm> {
+    let (LangType::Primitives(from), LangType::Primitives(to)) = (from, to) else {
+        panic!("Unsupported cast from {:?} to {:?}", from, to);
+    };
+
+    if from == to {
+        return value;
+    }
+
+    match (from, to) {
+        (f, t) if (f.is_integer() || *f == Primitive::Bool) && t.is_integer() => {
+            let int_value = value.into_int_value();
+            let target = target_llvm_ty.into_int_type();
+
+            if t.bit_width() < f.bit_width() {
+                builder.build_int_truncate(int_value, target, "trunc").unwrap().as_basic_value_enum()
+            } else if t.bit_width() > f.bit_width() && is_zero_extended(f) {
+                builder.build_int_z_extend(int_value, target, "zext").unwrap().as_basic_value_enum()
+            } else if t.bit_width() > f.bit_width() {
+                builder.build_int_s_extend(int_value, target, "sext").unwrap().as_basic_value_enum()
+            } else {
+                value
+            }
+        }
+
+        (f, t) if f.is_integer() && t.is_float() => {
+            if is_zero_extended(f) {
+                builder.build_unsigned_int_to_float(value.into_int_value(), target_llvm_ty.into_float_type(), "uitofp")
+                    .unwrap().as_basic_value_enum()
+            } else {
+                builder.build_signed_int_to_float(value.into_int_value(), target_llvm_ty.into_float_type(), "sitofp")
+                    .unwrap().as_basic_value_enum()
+            }
+        }
+
+        (f, t) if f.is_float() && t.is_integer() => {
+            if is_zero_extended(t) {
+                builder.build_float_to_unsigned_int(value.into_float_value(), target_llvm_ty.into_int_type(), "fptoui")
+                    .unwrap().as_basic_value_enum()
+            } else {
+                builder.build_float_to_signed_int(value.into_float_value(), target_llvm_ty.into_int_type(), "fptosi")
+                    .unwrap().as_basic_value_enum()
+            }
+        }
+
+        (f, t) if f.is_float() && t.is_float() => {
+            if t.bit_width() > f.bit_width() {
+                builder.build_float_ext(value.into_float_value(), target_llvm_ty.into_float_type(), "fpext")
+                    .unwrap().as_basic_value_enum()
+            } else if t.bit_width() < f.bit_width() {
+                builder.build_float_trunc(value.into_float_value(), target_llvm_ty.into_float_type(), "fptrunc")
+                    .unwrap().as_basic_value_enum()
+            } else {
+                value
+            }
+        }
+
+        _ => panic!("Unsupported cast from {:?} to {:?}", from, to)
     }
-}
\ No newline at end of file
+}
@@ -1,10 +1,10 @@
-use std::{collections::HashMap};
+use std::{collections::{BTreeMap, HashMap}};
 
-use hir::{HirExpr, HirExprKind, HirModuleItem, HirVisibility};
-use inkwell::{builder::Builder, context::Context, module::{Linkage, Module}, passes::PassBuilderOptions, targets::{InitializationConfig, Target, TargetMachine}, types::{BasicType, BasicTypeEnum}, values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum, FunctionValue, PointerValue}};
-use middle::{ty::{LangType, Primitive}, GlobalCtx};
+use hir::{HirExpr, HirExprKind, HirId, HirModuleItem, HirPattern, HirVariant, HirVisibility};
+use inkwell::{builder::Builder, context::Context, intrinsics::Intrinsic, module::{Linkage, Module}, passes::PassBuilderOptions, targets::{InitializationConfig, Target, TargetMachine}, types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType, StructType}, values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum, FunctionValue, IntValue, PointerValue}, AddressSpace, IntPredicate};
+use middle::{ty::{LangType, Primitive}, Capture, ConstValue, GlobalCtx};
 
-use crate::builder::build_llvm_binop;
+use crate::builder::{build_llvm_binop, build_llvm_cast};
 
 pub mod builder;
 
@@ -15,13 +15,16 @@ struct PtrValue<'ctx> {
 }
 
 struct VariableEnv<'ctx> {
-    scopes: Vec<HashMap<&'ctx str, PtrValue<'ctx>>>
+    scopes: Vec<HashMap<&'ctx str, PtrValue<'ctx>>>,
+    // deferred expressions of every scope, run in reverse order whenever the scope is left
+    cleanups: Vec<Vec<&'ctx HirExpr<'ctx>>>
 }
 
 impl<'ctx> VariableEnv<'ctx> {
     fn new() -> Self {
         let mut global = Self {
-            scopes: vec![]
+            scopes: vec![],
+            cleanups: vec![]
         };
         
         global.push_scope();
@@ -29,9 +32,21 @@ impl<'ctx> VariableEnv<'ctx> {
         global
     }
 
-    fn push_scope(&mut self) { self.scopes.push(HashMap::new()); }
+    fn push_scope(&mut self) { 
+        self.scopes.push(HashMap::new()); 
+        self.cleanups.push(Vec::new());
+    }
     
-    fn pop_scope(&mut self) { self.scopes.pop(); }
+    fn pop_scope(&mut self) { 
+        self.scopes.pop(); 
+        self.cleanups.pop();
+    }
+
+    fn depth(&self) -> usize { self.scopes.len() }
+
+    fn defer(&mut self, expr: &'ctx HirExpr<'ctx>) {
+        self.cleanups.last_mut().unwrap().push(expr);
+    }
 
     fn declare_variable(&mut self, name: &'ctx str, value: PtrValue<'ctx>) -> Result<(), String> {
         if self.scopes.last_mut().unwrap().insert(name, value).is_some() {
@@ -55,21 +70,81 @@ impl<'ctx> VariableEnv<'ctx> {
 
 fn translate_to_llvm_ty<'input>(context: &'input Context, basic_type: &LangType) -> BasicTypeEnum<'input> {
     match basic_type {
-        LangType::Primitives(Primitive::Int) => context.i64_type().as_basic_type_enum(),
-        LangType::Primitives(Primitive::Float) => context.f64_type().as_basic_type_enum(),
         LangType::Primitives(Primitive::Bool) => context.bool_type().as_basic_type_enum(),
-        LangType::Primitives(Primitive::Char) => context.i8_type().as_basic_type_enum(),
+        LangType::Primitives(Primitive::F32) => context.f32_type().as_basic_type_enum(),
+        LangType::Primitives(Primitive::Float | Primitive::F64) => context.f64_type().as_basic_type_enum(),
+        LangType::Primitives(p) if p.is_integer() => context.custom_width_int_type(p.bit_width()).as_basic_type_enum(),
+        LangType::Function { .. } => closure_struct_type(context).as_basic_type_enum(),
+        LangType::Newtype { ty, .. } => translate_to_llvm_ty(context, ty),
+        LangType::Dyn(_) => dyn_struct_type(context).as_basic_type_enum(),
+        LangType::Struct(name) => context.get_struct_type(name)
+            .unwrap_or_else(|| panic!("Unknown struct type {}", name))
+            .as_basic_type_enum(),
+        LangType::Tuple(elems) => {
+            let elem_types: Vec<BasicTypeEnum> = elems.iter()
+                .map(|elem| translate_to_llvm_ty(context, elem))
+                .collect();
+
+            context.struct_type(&elem_types, false).as_basic_type_enum()
+        }
+        LangType::StaticArray { size, ty } => translate_to_llvm_ty(context, ty).array_type(*size as u32).as_basic_type_enum(),
+        LangType::Slice(_) => slice_struct_type(context).as_basic_type_enum(),
+        // every copy of a vector points to the same header, so growing it through one is seen by all
+        LangType::Vec(_) => context.ptr_type(AddressSpace::default()).as_basic_type_enum(),
+        // a tag that is set for `Some` and `Ok` followed by the payloads
+        LangType::Option(ty) => context.struct_type(&[
+            context.bool_type().into(),
+            translate_to_llvm_ty(context, ty)
+        ], false).as_basic_type_enum(),
+        LangType::Result(ty, err) => context.struct_type(&[
+            context.bool_type().into(),
+            translate_to_llvm_ty(context, ty),
+            translate_to_llvm_ty(context, err)
+        ], false).as_basic_type_enum(),
         
         _ => panic!("Unsupported type: {:?}", basic_type),
     }
 }
 
+// every function value is a pair of a code pointer and a pointer to the captured environment
+fn closure_struct_type<'input>(context: &'input Context) -> StructType<'input> {
+    let ptr_type = context.ptr_type(AddressSpace::default());
+
+    context.struct_type(&[ptr_type.into(), ptr_type.into()], false)
+}
+
+// trait objects are a pointer to the boxed value and a pointer to the vtable of its type
+fn dyn_struct_type<'input>(context: &'input Context) -> StructType<'input> {
+    let ptr_type = context.ptr_type(AddressSpace::default());
+
+    context.struct_type(&[ptr_type.into(), ptr_type.into()], false)
+}
+
+// slices are a pointer to the first element and the element count
+fn slice_struct_type<'input>(context: &'input Context) -> StructType<'input> {
+    let ptr_type = context.ptr_type(AddressSpace::default());
+
+    context.struct_type(&[ptr_type.into(), context.i64_type().into()], false)
+}
+
+// the heap header of a vector, a buffer of `capacity` elements of which `len` are initialized
+fn vec_struct_type<'input>(context: &'input Context) -> StructType<'input> {
+    let ptr_type = context.ptr_type(AddressSpace::default());
+
+    context.struct_type(&[ptr_type.into(), context.i64_type().into(), context.i64_type().into()], false)
+}
+
 struct ModuleCodeGenerator<'llvm, 'global: 'llvm> {
     llvm_mod: Module<'llvm>,
     builder: Builder<'llvm>,
     llvm_ctx: &'llvm Context,
     env_variables: VariableEnv<'llvm>,
     func_env: HashMap<String, FunctionValue<'llvm>>,
+    fn_symbols: HashMap<HirId, String>,
+    fn_items: HashMap<HirId, &'global HirModuleItem<'global>>,
+    // type arguments of the generic function instance being generated
+    type_args: BTreeMap<String, LangType>,
+    closure_count: usize,
 
     global_ctx: &'global GlobalCtx<'global>,
 }
@@ -83,6 +158,64 @@ impl<'llvm, 'global: 'llvm> ModuleCodeGenerator<'llvm, 'global> {
             llvm_ctx: context,
             env_variables: VariableEnv::new(),
             func_env: HashMap::new(),
+            fn_symbols: collect_fn_symbols(global_ctx),
+            fn_items: collect_fn_items(global_ctx),
+            type_args: BTreeMap::new(),
+            closure_count: 0,
+        }
+    }
+
+    fn type_of(&self, id: &HirId) -> LangType {
+        self.global_ctx.module_ty_info.borrow().get_type(id).unwrap().ty.substitute(&self.type_args)
+    }
+
+    // the type after implicit coercions, which is where `None`, `Ok` and `Err` get their missing parts from
+    fn value_type_of(&self, id: &HirId) -> LangType {
+        match self.global_ctx.module_coercions.borrow().get_coercion(id) {
+            Some(target) => target.substitute(&self.type_args),
+            None => self.type_of(id)
+        }
+    }
+
+    fn symbol_name(&self, node: &HirExpr, fallback: &str) -> String {
+        self.global_ctx.module_symbols.borrow().get_pair(&node.id)
+            .and_then(|symbol| self.fn_symbols.get(&symbol.id))
+            .cloned()
+            .unwrap_or_else(|| fallback.to_string())
+    }
+
+    fn declare_struct_types(&self) {
+        let structs = self.global_ctx.module_structs.borrow();
+
+        for (name, _) in structs.iter() {
+            self.llvm_ctx.opaque_struct_type(name);
+        }
+
+        for (name, info) in structs.iter() {
+            let field_types: Vec<BasicTypeEnum<'llvm>> = info.fields.iter()
+                .map(|field| translate_to_llvm_ty(self.llvm_ctx, &field.1))
+                .collect();
+
+            self.llvm_ctx.get_struct_type(name).unwrap().set_body(&field_types, false);
+        }
+    }
+
+    fn translate_to_fn_type(&self, arg_types: &[LangType], return_type: &LangType, with_env: bool) -> FunctionType<'llvm> {
+        let mut argument_types: Vec<BasicMetadataTypeEnum<'llvm>> = Vec::new();
+
+        if with_env {
+            argument_types.push(self.llvm_ctx.ptr_type(AddressSpace::default()).into());
+        }
+
+        argument_types.extend(arg_types
+            .iter()
+            .map(|arg| -> BasicMetadataTypeEnum<'llvm> { translate_to_llvm_ty(self.llvm_ctx, arg).into() }));
+
+        match return_type {
+            LangType::Primitives(Primitive::Unit) => self.llvm_ctx.void_type().fn_type(&argument_types, false),
+            _ => 
+                translate_to_llvm_ty(self.llvm_ctx, return_type)
+                    .fn_type(&argument_types, false),
         }
     }
 
@@ -93,27 +226,22 @@ impl<'llvm, 'global: 'llvm> ModuleCodeGenerator<'llvm, 'global> {
         return_type: &LangType,
     ) -> FunctionValue<'llvm>{
 
+        // a call may declare the function before its definition is emitted, the definition decides the linkage
         if let Some(cached_fn) = self.func_env.get(name) {
-            return cached_fn.clone();
+            if is_global {
+                cached_fn.set_linkage(Linkage::External);
+            }
+
+            return *cached_fn;
         }
 
-        let argument_types: Vec<_> = arg_types
-                .iter()
-                .map(|arg| translate_to_llvm_ty(self.llvm_ctx, arg).into())
-                .collect();
-        
         let linkage = if is_global {
             Linkage::External
         } else {
             Linkage::Internal
         };
 
-        let func_signature = match return_type {
-            LangType::Primitives(Primitive::Unit) => self.llvm_ctx.void_type().fn_type(&argument_types, false),
-            _ => 
-                translate_to_llvm_ty(self.llvm_ctx, &return_type)
-                    .fn_type(&argument_types, false),
-        };
+        let func_signature = self.translate_to_fn_type(arg_types, return_type, false);
 
         let func = self.llvm_mod.add_function(name, func_signature, Some(linkage));
 
@@ -123,14 +251,44 @@ impl<'llvm, 'global: 'llvm> ModuleCodeGenerator<'llvm, 'global> {
     }
 
     fn generate_inner_decls_ir(&mut self, node: &'llvm HirExpr) -> BasicValueEnum<'llvm> {
+        let value = self.generate_expr_value(node);
+
+        let coercion = self.global_ctx.module_coercions.borrow().get_coercion(&node.id).cloned();
+
+        match coercion {
+            Some(LangType::Dyn(trait_name)) => {
+                let source_ty = self.type_of(&node.id);
+                self.build_dyn_value(value, &source_ty, &trait_name)
+            }
+            Some(LangType::Slice(_)) => {
+                let source_ty = self.type_of(&node.id);
+                self.build_slice_value(node, value, &source_ty)
+            }
+            _ => value
+        }
+    }
+
+    fn generate_expr_value(&mut self, node: &'llvm HirExpr) -> BasicValueEnum<'llvm> {
         match &node.kind {
-            HirExprKind::Int(val) => BasicValueEnum::IntValue(
-                self.llvm_ctx.i64_type().const_int(unsafe { std::mem::transmute(*val) }, false)
-            ),
+            HirExprKind::Int(val, negative, _) => {
+                let ty = self.type_of(&node.id);
+                let int_ty = translate_to_llvm_ty(self.llvm_ctx, &ty).into_int_type();
+
+                // two's complement bits of the literal, cut down to the width of its type
+                let bits = if *negative { val.wrapping_neg() } else { *val };
+
+                BasicValueEnum::IntValue(
+                    int_ty.const_int_arbitrary_precision(&[bits as u64, (bits >> 64) as u64])
+                )
+            }
             
-            HirExprKind::Float(val) => BasicValueEnum::FloatValue(
-                self.llvm_ctx.f64_type().const_float(*val)
-            ),
+            HirExprKind::Float(val, _) => {
+                let ty = self.type_of(&node.id);
+
+                BasicValueEnum::FloatValue(
+                    translate_to_llvm_ty(self.llvm_ctx, &ty).into_float_type().const_float(*val)
+                )
+            }
             
             HirExprKind::Bool(val) => {
                 if *val == true {
@@ -140,8 +298,24 @@ impl<'llvm, 'global: 'llvm> ModuleCodeGenerator<'llvm, 'global> {
                 }
             }
 
+            HirExprKind::Path { .. } => {
+                let value = self.lookup_const(node).unwrap();
+                let ty = self.type_of(&node.id);
+                self.translate_const_value(&value, &ty)
+            }
+
             HirExprKind::Id(id) =>  {
-                let val = self.env_variables.get_variable(&id).unwrap();
+                if let Some(value) = self.lookup_const(node) {
+                    let ty = self.type_of(&node.id);
+                    return self.translate_const_value(&value, &ty);
+                }
+
+                let Some(val) = self.env_variables.get_variable(&id) else {
+                    let fn_ty = self.type_of(&node.id);
+                    let symbol = self.symbol_name(node, id);
+                    return self.build_function_value(&symbol, &fn_ty);
+                };
+
                 self.builder.build_load(
                         val.value_type,
                         val.ptr, 
@@ -149,13 +323,15 @@ impl<'llvm, 'global: 'llvm> ModuleCodeGenerator<'llvm, 'global> {
             }
             
             HirExprKind::Binary { op, lhs, rhs } => {
+                let lhs_id = lhs.id;
                 let lhs = self.generate_inner_decls_ir(lhs);
 
                 let rhs = self.generate_inner_decls_ir(rhs);
                 
-                let ty = self.global_ctx.module_ty_info.borrow().get_type(&node.id).unwrap().ty.clone();
+                // comparisons are typed Bool, the instruction is chosen by the operands
+                let ty = self.value_type_of(&lhs_id);
 
-                build_llvm_binop(&self.builder, lhs, rhs, op, &ty)
+                build_llvm_binop(&self.builder, lhs, rhs, op, ty.underlying())
             }
 
             HirExprKind::Block(block) => {
@@ -163,48 +339,77 @@ impl<'llvm, 'global: 'llvm> ModuleCodeGenerator<'llvm, 'global> {
                 
                 let mut return_expr: Option<BasicValueEnum<'llvm>> = None;
                 
-                block.iter().for_each(|f| {
+                // statements after a `ret` are never reached
+                for f in block {
+                    if self.builder.get_insert_block().unwrap().get_terminator().is_some() {
+                        break;
+                    }
+
                     return_expr = Some(self.generate_inner_decls_ir(f));
-                });
+                }
+
+                if self.builder.get_insert_block().unwrap().get_terminator().is_none() {
+                    self.emit_cleanups(self.env_variables.depth() - 1);
+                }
                 
                 self.env_variables.pop_scope();
                 
-                return_expr.unwrap()
+                return_expr.unwrap_or_else(|| self.default_val())
             }
 
             HirExprKind::Return(expr) => {
                 if let Some(expr) = expr {
                     let value = self.generate_inner_decls_ir(expr);
+                    self.emit_cleanups(0);
                     self.builder.build_return(Some(&value)).unwrap();
                 } else {
+                    self.emit_cleanups(0);
                     self.builder.build_return(None).unwrap();
                 }
                 
                 self.default_val()
             }
 
-            HirExprKind::Call { name, args} => {
-                let arg_types: Vec<LangType> = args.iter()
-                    .map(|arg| {
-                        self.global_ctx.module_ty_info.borrow()
-                            .get_type(&arg.id)
-                            .map(|ti| ti.ty.clone())
-                            .unwrap()
-                    })
-                    .collect();
+            HirExprKind::Call { alias, name, args} => {
+                if alias.is_none() {
+                    if let Some(callee) = self.env_variables.get_variable(name) {
+                        return self.build_indirect_call(node, name, callee, args);
+                    }
+                }
+
+                let mut symbol = self.symbol_name(node, name);
+
+                let callee = self.global_ctx.module_symbols.borrow().get_pair(&node.id).unwrap().clone();
+                let mut callee_ty = self.type_of(&callee.id);
+
+                let instance = self.global_ctx.module_instances.borrow().get_instance(&node.id).cloned();
+
+                if let Some(type_args) = instance {
+                    let type_args: BTreeMap<String, LangType> = type_args.into_iter()
+                        .map(|(param, ty)| (param, ty.substitute(&self.type_args)))
+                        .collect();
+
+                    symbol = instance_symbol(&symbol, &type_args);
+                    callee_ty = callee_ty.substitute(&type_args);
+
+                    self.instantiate(callee.id, &symbol, type_args);
+                }
+
+                // the declaration follows the callee, arguments are already coerced to its parameter types
+                let LangType::Function { params, ret } = callee_ty else {
+                    panic!("{} has type {:?} and can not be called", name, callee_ty);
+                };
 
                 let llvm_args: Vec<BasicMetadataValueEnum<'llvm>> = args.iter()
                     .map(|arg| self.generate_inner_decls_ir(arg).into())
                     .collect();
 
-                let func_ty = self.global_ctx.module_ty_info.borrow().get_type(&node.id).unwrap().clone();
-                let return_ty = func_ty.ty.clone();
-            
+                // functions of imported modules are defined in their own objects
                 let function = self.translate_to_function_sig(
-                    name,
-                    &arg_types,
-                    false, 
-                    &return_ty,
+                    &symbol,
+                    &params,
+                    callee.is_external_name(),
+                    &ret,
                 );
 
                 let call = self.builder.build_call(
@@ -219,6 +424,258 @@ impl<'llvm, 'global: 'llvm> ModuleCodeGenerator<'llvm, 'global> {
                 }
             }
 
+            HirExprKind::MethodCall { receiver, method, args } => {
+                // generic receivers are only known here, so dispatch goes through the receiver type
+                let receiver_ty = self.type_of(&receiver.id);
+
+                if receiver_ty.has_builtin_methods() {
+                    return self.build_builtin_method(receiver, &receiver_ty, method, args);
+                }
+
+                if let LangType::Dyn(trait_name) = &receiver_ty {
+                    return self.build_dyn_call(receiver, trait_name, method, args);
+                }
+
+                let LangType::Struct(ty_name) = &receiver_ty else {
+                    panic!("method {} called on value of type {:?}", method, receiver_ty);
+                };
+
+                let method_id = self.global_ctx.module_methods.borrow().get_method(ty_name, method)
+                    .unwrap_or_else(|| panic!("no method {} found for type {}", method, ty_name))
+                    .id;
+
+                let method_ty = self.type_of(&method_id);
+
+                let LangType::Function { params, ret } = method_ty else {
+                    panic!("method {} has non function type {:?}", method, method_ty);
+                };
+
+                let symbol = self.fn_symbols[&method_id].clone();
+                let function = self.translate_to_function_sig(&symbol, &params, false, &ret);
+
+                let mut llvm_args: Vec<BasicMetadataValueEnum<'llvm>> = vec![self.generate_inner_decls_ir(receiver).into()];
+
+                for arg in args {
+                    llvm_args.push(self.generate_inner_decls_ir(arg).into());
+                }
+
+                let call = self.builder.build_call(function, llvm_args.as_slice(), method).unwrap();
+
+                match call.try_as_basic_value().left() {
+                    Some(value) => value,
+                    None => self.default_val(),
+                }
+            }
+
+            HirExprKind::StructLiteral { name, fields } => {
+                let struct_ty = translate_to_llvm_ty(self.llvm_ctx, &LangType::Struct(name.to_string())).into_struct_type();
+                let info = self.global_ctx.module_structs.borrow().get(name).cloned().unwrap();
+
+                let mut value = struct_ty.get_undef();
+
+                for (field, field_expr) in fields {
+                    let (index, _) = info.field(field).unwrap();
+                    let field_value = self.generate_inner_decls_ir(field_expr);
+
+                    value = self.builder.build_insert_value(value, field_value, index as u32, field).unwrap()
+                        .into_struct_value();
+                }
+
+                value.as_basic_value_enum()
+            }
+
+            HirExprKind::FieldAccess { expr, field } => {
+                let struct_ty = self.type_of(&expr.id);
+
+                let LangType::Struct(struct_name) = struct_ty else {
+                    panic!("field {} accessed on value of type {:?}", field, struct_ty);
+                };
+
+                let (index, _) = self.global_ctx.module_structs.borrow().get(&struct_name).unwrap()
+                    .field(field)
+                    .unwrap();
+
+                let value = self.generate_inner_decls_ir(expr).into_struct_value();
+
+                self.builder.build_extract_value(value, index as u32, field).unwrap()
+            }
+
+            HirExprKind::Str(text) => {
+                let data = self.builder.build_global_string_ptr(text, "str").unwrap().as_pointer_value();
+                let len = self.llvm_ctx.i64_type().const_int(text.len() as u64, false);
+
+                self.build_slice(data, len)
+            }
+
+            HirExprKind::Defer(expr) => {
+                self.env_variables.defer(expr);
+                self.default_val()
+            }
+
+            HirExprKind::VecNew => {
+                let header_ty = vec_struct_type(self.llvm_ctx);
+
+                let header = self.builder.build_malloc(header_ty, "vec").unwrap();
+                self.builder.build_store(header, header_ty.const_zero()).unwrap();
+
+                header.as_basic_value_enum()
+            }
+
+            HirExprKind::Variant { variant, value } => {
+                let variant_ty = translate_to_llvm_ty(self.llvm_ctx, &self.value_type_of(&node.id)).into_struct_type();
+                let tag = self.llvm_ctx.bool_type().const_int(matches!(variant, HirVariant::Some | HirVariant::Ok) as u64, false);
+
+                let mut result = self.builder.build_insert_value(variant_ty.const_zero(), tag, 0, "tag").unwrap()
+                    .into_struct_value();
+
+                if let Some(value) = value {
+                    let index = if *variant == HirVariant::Err { 2 } else { 1 };
+                    let payload = self.generate_inner_decls_ir(value);
+
+                    result = self.builder.build_insert_value(result, payload, index, "payload").unwrap()
+                        .into_struct_value();
+                }
+
+                result.as_basic_value_enum()
+            }
+
+            HirExprKind::Try(inner) => {
+                let inner_ty = self.value_type_of(&inner.id);
+                let value = self.generate_inner_decls_ir(inner).into_struct_value();
+
+                let function = self.builder.get_insert_block().unwrap().get_parent().unwrap();
+
+                let fail_block = self.llvm_ctx.append_basic_block(function, "try_fail");
+                let ok_block = self.llvm_ctx.append_basic_block(function, "try_ok");
+
+                let tag = self.builder.build_extract_value(value, 0, "tag").unwrap().into_int_value();
+                self.builder.build_conditional_branch(tag, ok_block, fail_block).unwrap();
+
+                // the checker made sure the function returns an option or a result with the same error type
+                self.builder.position_at_end(fail_block);
+
+                let ret_ty = function.get_type().get_return_type().unwrap().into_struct_type();
+                let mut early = ret_ty.const_zero();
+
+                if let LangType::Result(..) = inner_ty {
+                    let err = self.builder.build_extract_value(value, 2, "err").unwrap();
+
+                    early = self.builder.build_insert_value(early, err, 2, "err").unwrap()
+                        .into_struct_value();
+                }
+
+                self.emit_cleanups(0);
+                self.builder.build_return(Some(&early)).unwrap();
+
+                self.builder.position_at_end(ok_block);
+                self.builder.build_extract_value(value, 1, "payload").unwrap()
+            }
+
+            HirExprKind::Array(elems) => {
+                let array_ty = translate_to_llvm_ty(self.llvm_ctx, &self.type_of(&node.id)).into_array_type();
+
+                let mut value = array_ty.get_undef();
+
+                for (index, elem) in elems.iter().enumerate() {
+                    let elem_value = self.generate_inner_decls_ir(elem);
+
+                    value = self.builder.build_insert_value(value, elem_value, index as u32, "array").unwrap()
+                        .into_array_value();
+                }
+
+                value.as_basic_value_enum()
+            }
+
+            HirExprKind::Index { expr, index } => {
+                let container_ty = self.type_of(&expr.id);
+                let elem_ty = translate_to_llvm_ty(self.llvm_ctx, &self.type_of(&node.id));
+
+                let container = self.generate_inner_decls_ir(expr);
+                let (data, len) = self.elements_of(container, &container_ty);
+
+                let index_ty = self.type_of(&index.id);
+                let index = self.generate_inner_decls_ir(index).into_int_value();
+
+                let is_signed = matches!(&index_ty, LangType::Primitives(p) if !p.is_unsigned());
+                let index = self.builder.build_int_cast_sign_flag(index, self.llvm_ctx.i64_type(), is_signed, "index").unwrap();
+
+                self.build_bounds_check(index, len);
+
+                let elem_ptr = unsafe {
+                    self.builder.build_in_bounds_gep(elem_ty, data, &[index], "elem_ptr").unwrap()
+                };
+
+                self.builder.build_load(elem_ty, elem_ptr, "elem").unwrap()
+            }
+
+            HirExprKind::For { pattern, iterable, body } => {
+                let iterable_ty = self.type_of(&iterable.id);
+                let elem_ty = translate_to_llvm_ty(self.llvm_ctx, iterable_ty.element_type().unwrap());
+
+                let iterable_value = self.generate_inner_decls_ir(iterable);
+                let (data, len) = self.elements_of(iterable_value, &iterable_ty);
+
+                let i64_type = self.llvm_ctx.i64_type();
+                let counter = self.build_entry_alloca(i64_type.into(), "for_index");
+                self.builder.build_store(counter, i64_type.const_zero()).unwrap();
+
+                let function = self.builder.get_insert_block().unwrap().get_parent().unwrap();
+
+                let cond_block = self.llvm_ctx.append_basic_block(function, "for_cond");
+                let body_block = self.llvm_ctx.append_basic_block(function, "for_body");
+                let end_block = self.llvm_ctx.append_basic_block(function, "for_end");
+
+                self.builder.build_unconditional_branch(cond_block).unwrap();
+
+                self.builder.position_at_end(cond_block);
+                let index = self.builder.build_load(i64_type, counter, "index").unwrap().into_int_value();
+                let in_range = self.builder.build_int_compare(IntPredicate::ULT, index, len, "in_range").unwrap();
+                self.builder.build_conditional_branch(in_range, body_block, end_block).unwrap();
+
+                self.builder.position_at_end(body_block);
+
+                let elem_ptr = unsafe {
+                    self.builder.build_in_bounds_gep(elem_ty, data, &[index], "elem_ptr").unwrap()
+                };
+                let elem = self.builder.build_load(elem_ty, elem_ptr, "elem").unwrap();
+
+                self.env_variables.push_scope();
+                self.bind_pattern(pattern, elem);
+                self.generate_inner_decls_ir(body);
+                self.env_variables.pop_scope();
+
+                if self.builder.get_insert_block().unwrap().get_terminator().is_none() {
+                    let next = self.builder.build_int_add(index, i64_type.const_int(1, false), "next_index").unwrap();
+                    self.builder.build_store(counter, next).unwrap();
+                    self.builder.build_unconditional_branch(cond_block).unwrap();
+                }
+
+                self.builder.position_at_end(end_block);
+
+                self.default_val()
+            }
+
+            HirExprKind::Tuple(elems) => {
+                let tuple_ty = translate_to_llvm_ty(self.llvm_ctx, &self.type_of(&node.id)).into_struct_type();
+
+                let mut value = tuple_ty.get_undef();
+
+                for (index, elem) in elems.iter().enumerate() {
+                    let elem_value = self.generate_inner_decls_ir(elem);
+
+                    value = self.builder.build_insert_value(value, elem_value, index as u32, "tuple").unwrap()
+                        .into_struct_value();
+                }
+
+                value.as_basic_value_enum()
+            }
+
+            HirExprKind::TupleIndex { expr, index } => {
+                let value = self.generate_inner_decls_ir(expr).into_struct_value();
+
+                self.builder.build_extract_value(value, *index as u32, "tuple_elem").unwrap()
+            }
+
             
             HirExprKind::If { cond, then, _else } => {
                 let condition = self.generate_inner_decls_ir(&cond).into_int_value();
@@ -226,56 +683,57 @@ impl<'llvm, 'global: 'llvm> ModuleCodeGenerator<'llvm, 'global> {
                 let current_block = self.builder.get_insert_block().unwrap();
                 let function = current_block.get_parent().unwrap();
                 
-                let result_type = translate_to_llvm_ty(
-                    self.llvm_ctx, 
-                    &self.global_ctx.module_ty_info.borrow().get_type(&node.id).unwrap().ty
-                );
-                
-                let result_alloca = self.builder.build_alloca(result_type, "if_result").unwrap();
+                // an `if` without a value, one without `else` included, only runs its branches
+                let result_ty = self.value_type_of(&node.id);
+
+                let result = if result_ty.is_unit() {
+                    None
+                } else {
+                    let result_type = translate_to_llvm_ty(self.llvm_ctx, &result_ty);
+                    Some((result_type, self.build_entry_alloca(result_type, "if_result")))
+                };
 
                 let then_block = self.llvm_ctx.append_basic_block(function, "then");
                 let else_block = self.llvm_ctx.append_basic_block(function, "else");
                 let merge_block = self.llvm_ctx.append_basic_block(function, "merge");
                 
                 self.builder.build_conditional_branch(condition, then_block, else_block).unwrap();
+
+                let mut reaches_merge = false;
                 
-                self.builder.position_at_end(then_block);
-                let then_val = self.generate_inner_decls_ir(&then);
-                
-                if self.builder.get_insert_block().unwrap().get_terminator().is_none() {
-                    self.builder.build_store(result_alloca, then_val).unwrap();
-                    self.builder.build_unconditional_branch(merge_block).unwrap();
-                }
-                
-                self.builder.position_at_end(else_block);
-                let else_val = if let Some(else_expr) = _else {
-                    self.generate_inner_decls_ir(else_expr)
-                } else {
-                    match result_type {
-                        BasicTypeEnum::IntType(int_ty) => int_ty.const_zero().as_basic_value_enum(),
-                        BasicTypeEnum::FloatType(float_ty) => float_ty.const_float(0.0).as_basic_value_enum(),
-                        _ => self.default_val(),
+                for (block, branch) in [(then_block, Some(then)), (else_block, _else.as_ref())] {
+                    self.builder.position_at_end(block);
+
+                    let value = branch.map(|branch| self.generate_inner_decls_ir(branch));
+
+                    if self.builder.get_insert_block().unwrap().get_terminator().is_some() {
+                        continue;
                     }
-                };
-                
-                if self.builder.get_insert_block().unwrap().get_terminator().is_none() {
-                    self.builder.build_store(result_alloca, else_val).unwrap();
+
+                    if let (Some((_, result_alloca)), Some(value)) = (result, value) {
+                        self.builder.build_store(result_alloca, value).unwrap();
+                    }
+
                     self.builder.build_unconditional_branch(merge_block).unwrap();
+                    reaches_merge = true;
                 }
                 
                 self.builder.position_at_end(merge_block);
+
+                if !reaches_merge {
+                    self.builder.build_unreachable().unwrap();
+                    return self.default_val();
+                }
                 
-                if merge_block.get_first_instruction().is_some() || 
-                merge_block.get_terminator().is_some() {
-                    self.builder.build_load(result_type, result_alloca, "if_result").unwrap()
-                } else {
-                    self.default_val()
+                match result {
+                    Some((result_type, result_alloca)) => self.builder.build_load(result_type, result_alloca, "if_result").unwrap(),
+                    None => self.default_val()
                 }
             }
 
             HirExprKind::VarDef { name, value, ..} => {
                 let val: BasicValueEnum<'_> = self.generate_inner_decls_ir(&value);
-                let alloca = self.builder.build_alloca(val.get_type(), name).unwrap();
+                let alloca = self.build_entry_alloca(val.get_type(), name);
 
                 self.builder.build_store(alloca, val).unwrap();
 
@@ -287,89 +745,895 @@ impl<'llvm, 'global: 'llvm> ModuleCodeGenerator<'llvm, 'global> {
                 self.default_val()
             }
 
-            _ => panic!("Unsupported node: {:?}", node),
-        }
-    }
+            HirExprKind::Destructure { pattern, value, .. } => {
+                let val = self.generate_inner_decls_ir(value);
 
-    fn default_val(&self) -> BasicValueEnum<'llvm> {
-        BasicValueEnum::IntValue(self.llvm_ctx.i64_type().const_zero())
-    }
+                self.bind_pattern(pattern, val);
 
-    fn generate_ir(&mut self) {
-        for file in &self.global_ctx.module_files {
-            for decl in &file.items {
-                match decl {
-                    HirModuleItem::Func { id, name, args, body, visibility, .. } => {
-                        let is_global = match visibility {
-                            HirVisibility::Public => true,
-                            HirVisibility::Private => false
-                        };
-
-                        let ret_ty = self.global_ctx.module_ty_info.borrow().get_type(id).cloned().unwrap();
-
-                        let arg_types: Vec<_> = args.iter()
-                            .map(|(_, arg_id, _)| {
-                                self.global_ctx.module_ty_info.borrow().get_type(arg_id).unwrap().ty.clone()
-                            })
-                            .collect();
-
-                        let signature = self.translate_to_function_sig(
-                            &name, 
-                            &arg_types, 
-                            is_global, 
-                            &ret_ty.ty
-                        );
-
-                        let llvm_block = self.llvm_ctx.append_basic_block(signature, "start");
-                        self.builder.position_at_end(llvm_block);
-
-                        for i in 0..args.len() {
-                            let param = signature.get_nth_param(i as u32).unwrap();
-                            param.set_name(args[i].0);
-
-                            let alloca = self.builder.build_alloca(param.get_type(), args[i].0).unwrap();
-
-                            self.builder.build_store(alloca, param).unwrap();
-
-                            self.env_variables.declare_variable(args[i].0, PtrValue { 
-                                ptr: alloca, 
-                                value_type:  param.get_type()
-                            }).unwrap();
-                        }
+                self.default_val()
+            }
 
-                        self.generate_inner_decls_ir(&body);
+            HirExprKind::Cast { expr, ty: _ } => {
+                let value = self.generate_inner_decls_ir(expr);
 
-                        let current_block = self.builder.get_insert_block().unwrap();
+                let from_ty = self.type_of(&expr.id);
+                let to_ty = self.type_of(&node.id);
 
-                        if current_block.get_terminator().is_none() {
-                            if matches!(ret_ty.ty, LangType::Primitives(Primitive::Unit)) {
-                                self.builder.build_return(None).unwrap();
-                            } else {
-                                self.builder.build_unreachable().unwrap();
-                            }
-                        }
-                    }
-                }
+                build_llvm_cast(&self.builder, value, from_ty.underlying(), to_ty.underlying(), translate_to_llvm_ty(self.llvm_ctx, &to_ty))
             }
-        }
-    }
-}
 
-pub fn generate_object_code<'a, 'b>(ctx: &'a GlobalCtx<'a>) -> Vec<u8> {
-    let llvm_ctx = Context::create();
+            HirExprKind::Closure { args, body } => {
+                let closure_ty = self.type_of(&node.id);
 
-    Target::initialize_all(&InitializationConfig::default());
+                let captures = self.global_ctx.module_captures.borrow().get_captures(&node.id).to_vec();
 
-    let triple = TargetMachine::get_default_triple();
-    let cpu_features = TargetMachine::get_host_cpu_features();
-    let cpu_name = TargetMachine::get_host_cpu_name();
-    let target = Target::from_triple(&triple).unwrap();
+                let captured: Vec<PtrValue<'llvm>> = captures.iter()
+                    .map(|capture| self.env_variables.get_variable(capture.name).unwrap())
+                    .collect();
 
-    let machine = target
-        .create_target_machine(
-            &triple,
-            cpu_name.to_str().unwrap(),
-            cpu_features.to_str().unwrap(),
+                let env_ty = self.llvm_ctx.struct_type(
+                    &captured.iter().map(|value| value.value_type).collect::<Vec<_>>(), 
+                    false
+                );
+
+                let env_ptr = if captured.is_empty() {
+                    self.llvm_ctx.ptr_type(AddressSpace::default()).const_null()
+                } else {
+                    // captured values are copied, so the closure may outlive the enclosing frame
+                    // TODO: the environment is never freed. A closure can be returned, stored or copied, so freeing
+                    // it through the scope cleanups would leave dangling copies, it needs ownership of closures first
+                    let env_ptr = self.builder.build_malloc(env_ty, "closure_env").unwrap();
+
+                    for (i, value) in captured.iter().enumerate() {
+                        let loaded = self.builder.build_load(value.value_type, value.ptr, "captured").unwrap();
+                        let field = self.builder.build_struct_gep(env_ty, env_ptr, i as u32, "env_field").unwrap();
+                        self.builder.build_store(field, loaded).unwrap();
+                    }
+
+                    env_ptr
+                };
+
+                let function = self.generate_closure_fn(&closure_ty, args, body, &captures, env_ty);
+
+                self.build_closure_value(function, env_ptr)
+            }
+
+            _ => panic!("Unsupported node: {:?}", node),
+        }
+    }
+
+    fn bind_arguments(&mut self, function: FunctionValue<'llvm>, names: &[&'llvm str], offset: u32) {
+        for (i, name) in names.iter().enumerate() {
+            // unnamed parameters are only reachable through their patterns
+            if *name == "_" {
+                continue;
+            }
+
+            let param = function.get_nth_param(i as u32 + offset).unwrap();
+            param.set_name(name);
+
+            let alloca = self.builder.build_alloca(param.get_type(), name).unwrap();
+
+            self.builder.build_store(alloca, param).unwrap();
+
+            self.env_variables.declare_variable(name, PtrValue { 
+                ptr: alloca, 
+                value_type:  param.get_type()
+            }).unwrap();
+        }
+    }
+
+    // runs the deferred expressions of every scope from the innermost one down to `depth`
+    fn emit_cleanups(&mut self, depth: usize) {
+        for level in (depth..self.env_variables.depth()).rev() {
+            // taken out while emitted, so a `ret` inside deferred code only runs the outer cleanups
+            let deferred = std::mem::take(&mut self.env_variables.cleanups[level]);
+
+            // deferred code sees the names of its own scope and not the ones declared in nested scopes
+            let inner_scopes = self.env_variables.scopes.split_off(level + 1);
+            let inner_cleanups = self.env_variables.cleanups.split_off(level + 1);
+
+            for expr in deferred.iter().rev() {
+                self.generate_inner_decls_ir(expr);
+            }
+
+            self.env_variables.scopes.extend(inner_scopes);
+            self.env_variables.cleanups.extend(inner_cleanups);
+            self.env_variables.cleanups[level] = deferred;
+        }
+    }
+
+    // allocas live in the entry block, so locals declared inside loops do not grow the stack
+    fn build_entry_alloca(&self, ty: BasicTypeEnum<'llvm>, name: &str) -> PointerValue<'llvm> {
+        let function = self.builder.get_insert_block().unwrap().get_parent().unwrap();
+        let entry = function.get_first_basic_block().unwrap();
+
+        let entry_builder = self.llvm_ctx.create_builder();
+
+        match entry.get_first_instruction() {
+            Some(first) => entry_builder.position_before(&first),
+            None => entry_builder.position_at_end(entry),
+        }
+
+        entry_builder.build_alloca(ty, name).unwrap()
+    }
+
+    fn build_slice(&self, data: PointerValue<'llvm>, len: IntValue<'llvm>) -> BasicValueEnum<'llvm> {
+        let slice = slice_struct_type(self.llvm_ctx).get_undef();
+
+        let slice = self.builder.build_insert_value(slice, data, 0, "slice_data").unwrap()
+            .into_struct_value();
+
+        self.builder.build_insert_value(slice, len, 1, "slice_len").unwrap()
+            .into_struct_value()
+            .as_basic_value_enum()
+    }
+
+    fn build_slice_value(&mut self, node: &'llvm HirExpr, value: BasicValueEnum<'llvm>, source_ty: &LangType) -> BasicValueEnum<'llvm> {
+        match source_ty {
+            // a slice views the storage of the array, a variable in place and a temporary in a slot of the current frame
+            LangType::StaticArray { size, .. } => {
+                let variable = match node.kind {
+                    HirExprKind::Id(name) if self.lookup_const(node).is_none() => self.env_variables.get_variable(name),
+                    _ => None
+                };
+
+                let data = match variable {
+                    Some(variable) => variable.ptr,
+                    None => {
+                        let slot = self.build_entry_alloca(value.get_type(), "array");
+                        self.builder.build_store(slot, value).unwrap();
+                        slot
+                    }
+                };
+
+                self.build_slice(data, self.llvm_ctx.i64_type().const_int(*size, false))
+            }
+
+            LangType::Vec(_) => {
+                let (data, len) = self.elements_of(value, source_ty);
+                self.build_slice(data, len)
+            }
+
+            _ => panic!("value of type {:?} can not be viewed as a slice", source_ty)
+        }
+    }
+
+    // pointer to the first element and element count of an array, slice or vector
+    fn elements_of(&mut self, value: BasicValueEnum<'llvm>, ty: &LangType) -> (PointerValue<'llvm>, IntValue<'llvm>) {
+        match ty {
+            LangType::StaticArray { size, .. } => {
+                let data = self.build_entry_alloca(value.get_type(), "array");
+                self.builder.build_store(data, value).unwrap();
+
+                (data, self.llvm_ctx.i64_type().const_int(*size, false))
+            }
+
+            LangType::Vec(_) => {
+                let header = self.builder.build_load(vec_struct_type(self.llvm_ctx), value.into_pointer_value(), "vec").unwrap()
+                    .into_struct_value();
+
+                let data = self.builder.build_extract_value(header, 0, "data").unwrap().into_pointer_value();
+                let len = self.builder.build_extract_value(header, 1, "len").unwrap().into_int_value();
+
+                (data, len)
+            }
+
+            LangType::Slice(_) => {
+                let value = value.into_struct_value();
+
+                let data = self.builder.build_extract_value(value, 0, "data").unwrap().into_pointer_value();
+                let len = self.builder.build_extract_value(value, 1, "len").unwrap().into_int_value();
+
+                (data, len)
+            }
+
+            _ => panic!("value of type {:?} has no elements", ty)
+        }
+    }
+
+    fn build_bounds_check(&mut self, index: IntValue<'llvm>, len: IntValue<'llvm>) {
+        let in_bounds = self.builder.build_int_compare(IntPredicate::ULT, index, len, "in_bounds").unwrap();
+        self.build_trap_unless(in_bounds);
+    }
+
+    fn build_trap_unless(&mut self, cond: IntValue<'llvm>) {
+        let function = self.builder.get_insert_block().unwrap().get_parent().unwrap();
+
+        let fail_block = self.llvm_ctx.append_basic_block(function, "check_failed");
+        let ok_block = self.llvm_ctx.append_basic_block(function, "check_passed");
+
+        self.builder.build_conditional_branch(cond, ok_block, fail_block).unwrap();
+
+        self.builder.position_at_end(fail_block);
+
+        let trap = Intrinsic::find("llvm.trap").unwrap()
+            .get_declaration(&self.llvm_mod, &[])
+            .unwrap();
+
+        self.builder.build_call(trap, &[], "").unwrap();
+        self.builder.build_unreachable().unwrap();
+
+        self.builder.position_at_end(ok_block);
+    }
+
+    fn runtime_fn(&self, name: &str, fn_type: FunctionType<'llvm>) -> FunctionValue<'llvm> {
+        self.llvm_mod.get_function(name)
+            .unwrap_or_else(|| self.llvm_mod.add_function(name, fn_type, Some(Linkage::External)))
+    }
+
+    fn build_builtin_method(&mut self, receiver: &'llvm HirExpr, receiver_ty: &LangType, method: &str, args: &'llvm [HirExpr]) -> BasicValueEnum<'llvm> {
+        match method {
+            "len" => {
+                let value = self.generate_inner_decls_ir(receiver);
+                let (_, len) = self.elements_of(value, receiver_ty);
+
+                len.as_basic_value_enum()
+            }
+
+            "push" => {
+                let header = self.generate_inner_decls_ir(receiver).into_pointer_value();
+                let elem = self.generate_inner_decls_ir(&args[0]);
+
+                self.build_vec_push(header, elem);
+
+                self.default_val()
+            }
+
+            "is_some" | "is_ok" => {
+                let value = self.generate_inner_decls_ir(receiver).into_struct_value();
+                self.builder.build_extract_value(value, 0, "tag").unwrap()
+            }
+
+            "is_none" | "is_err" => {
+                let value = self.generate_inner_decls_ir(receiver).into_struct_value();
+                let tag = self.builder.build_extract_value(value, 0, "tag").unwrap().into_int_value();
+
+                self.builder.build_not(tag, "not_tag").unwrap().as_basic_value_enum()
+            }
+
+            "unwrap" => {
+                let value = self.generate_inner_decls_ir(receiver).into_struct_value();
+                let tag = self.builder.build_extract_value(value, 0, "tag").unwrap().into_int_value();
+
+                self.build_trap_unless(tag);
+                self.builder.build_extract_value(value, 1, "payload").unwrap()
+            }
+
+            "unwrap_err" => {
+                let value = self.generate_inner_decls_ir(receiver).into_struct_value();
+                let tag = self.builder.build_extract_value(value, 0, "tag").unwrap().into_int_value();
+                let is_err = self.builder.build_not(tag, "is_err").unwrap();
+
+                self.build_trap_unless(is_err);
+                self.builder.build_extract_value(value, 2, "err").unwrap()
+            }
+
+            _ => panic!("no method {} found for type {:?}", method, receiver_ty)
+        }
+    }
+
+    // grows the buffer by doubling its capacity through the runtime allocator when it is full
+    fn build_vec_push(&mut self, header: PointerValue<'llvm>, elem: BasicValueEnum<'llvm>) {
+        let vec_ty = vec_struct_type(self.llvm_ctx);
+        let i64_type = self.llvm_ctx.i64_type();
+        let ptr_type = self.llvm_ctx.ptr_type(AddressSpace::default());
+
+        let function = self.builder.get_insert_block().unwrap().get_parent().unwrap();
+
+        let grow_block = self.llvm_ctx.append_basic_block(function, "vec_grow");
+        let store_block = self.llvm_ctx.append_basic_block(function, "vec_store");
+
+        let vec = self.builder.build_load(vec_ty, header, "vec").unwrap().into_struct_value();
+        let len = self.builder.build_extract_value(vec, 1, "len").unwrap().into_int_value();
+        let capacity = self.builder.build_extract_value(vec, 2, "capacity").unwrap().into_int_value();
+
+        let is_full = self.builder.build_int_compare(IntPredicate::EQ, len, capacity, "is_full").unwrap();
+        self.builder.build_conditional_branch(is_full, grow_block, store_block).unwrap();
+
+        self.builder.position_at_end(grow_block);
+
+        let data = self.builder.build_extract_value(vec, 0, "data").unwrap().into_pointer_value();
+
+        let is_empty = self.builder.build_int_compare(IntPredicate::EQ, capacity, i64_type.const_zero(), "is_empty").unwrap();
+        let doubled = self.builder.build_int_mul(capacity, i64_type.const_int(2, false), "doubled").unwrap();
+        let new_capacity = self.builder.build_select(is_empty, i64_type.const_int(4, false), doubled, "new_capacity").unwrap()
+            .into_int_value();
+
+        let elem_size = elem.get_type().size_of().unwrap();
+        let elem_size = self.builder.build_int_cast(elem_size, i64_type, "elem_size").unwrap();
+        let byte_size = self.builder.build_int_mul(new_capacity, elem_size, "byte_size").unwrap();
+
+        let realloc = self.runtime_fn("realloc", ptr_type.fn_type(&[ptr_type.into(), i64_type.into()], false));
+        let new_data = self.builder.build_call(realloc, &[data.into(), byte_size.into()], "new_data").unwrap()
+            .try_as_basic_value()
+            .left()
+            .unwrap();
+
+        let grown = self.builder.build_insert_value(vec, new_data, 0, "grown").unwrap()
+            .into_struct_value();
+        let grown = self.builder.build_insert_value(grown, new_capacity, 2, "grown").unwrap()
+            .into_struct_value();
+        self.builder.build_store(header, grown).unwrap();
+        self.builder.build_unconditional_branch(store_block).unwrap();
+
+        self.builder.position_at_end(store_block);
+
+        let vec = self.builder.build_load(vec_ty, header, "vec").unwrap().into_struct_value();
+        let data = self.builder.build_extract_value(vec, 0, "data").unwrap().into_pointer_value();
+
+        let slot = unsafe {
+            self.builder.build_in_bounds_gep(elem.get_type(), data, &[len], "slot").unwrap()
+        };
+        self.builder.build_store(slot, elem).unwrap();
+
+        let new_len = self.builder.build_int_add(len, i64_type.const_int(1, false), "new_len").unwrap();
+        let vec = self.builder.build_insert_value(vec, new_len, 1, "vec").unwrap();
+        self.builder.build_store(header, vec.into_struct_value()).unwrap();
+    }
+
+    fn bind_pattern(&mut self, pattern: &'llvm HirPattern, value: BasicValueEnum<'llvm>) {
+        match pattern {
+            HirPattern::Binding(name, _) => {
+                let alloca = self.build_entry_alloca(value.get_type(), name);
+
+                self.builder.build_store(alloca, value).unwrap();
+
+                self.env_variables.declare_variable(name, PtrValue {
+                    ptr: alloca,
+                    value_type: value.get_type()
+                }).unwrap();
+            }
+
+            HirPattern::Wildcard => (),
+
+            HirPattern::Tuple(elems) => {
+                let tuple = value.into_struct_value();
+
+                for (index, elem) in elems.iter().enumerate() {
+                    let elem_value = self.builder.build_extract_value(tuple, index as u32, "tuple_elem").unwrap();
+                    self.bind_pattern(elem, elem_value);
+                }
+            }
+
+            HirPattern::Struct { name, fields } => {
+                let info = self.global_ctx.module_structs.borrow().get(name).cloned().unwrap();
+                let structure = value.into_struct_value();
+
+                for (field, field_pattern) in fields {
+                    let (index, _) = info.field(field).unwrap();
+                    let field_value = self.builder.build_extract_value(structure, index as u32, field).unwrap();
+                    self.bind_pattern(field_pattern, field_value);
+                }
+            }
+        }
+    }
+
+    fn build_closure_value(&self, function: FunctionValue<'llvm>, env_ptr: PointerValue<'llvm>) -> BasicValueEnum<'llvm> {
+        let closure = closure_struct_type(self.llvm_ctx).get_undef();
+
+        let closure = self.builder.build_insert_value(
+            closure, 
+            function.as_global_value().as_pointer_value(), 
+            0, 
+            "closure_fn"
+        ).unwrap().into_struct_value();
+
+        self.builder.build_insert_value(closure, env_ptr, 1, "closure_env").unwrap()
+            .into_struct_value()
+            .as_basic_value_enum()
+    }
+
+    // named functions do not expect an environment, so they are wrapped into a thunk that drops it
+    fn build_function_value(&mut self, name: &str, fn_ty: &LangType) -> BasicValueEnum<'llvm> {
+        let LangType::Function { params, ret } = fn_ty else {
+            panic!("{} has type {:?} and can not be used as a function", name, fn_ty);
+        };
+
+        let thunk_name = format!("{}$thunk", name);
+
+        let thunk = match self.func_env.get(&thunk_name) {
+            Some(thunk) => *thunk,
+            None => {
+                let target = self.translate_to_function_sig(name, params, false, ret);
+
+                let thunk_type = self.translate_to_fn_type(params, ret, true);
+                let thunk = self.llvm_mod.add_function(&thunk_name, thunk_type, Some(Linkage::Internal));
+                self.func_env.insert(thunk_name, thunk);
+
+                let saved_block = self.builder.get_insert_block();
+
+                let entry = self.llvm_ctx.append_basic_block(thunk, "start");
+                self.builder.position_at_end(entry);
+
+                let forwarded: Vec<BasicMetadataValueEnum<'llvm>> = thunk.get_param_iter()
+                    .skip(1)
+                    .map(|param| param.into())
+                    .collect();
+
+                let call = self.builder.build_call(target, &forwarded, "forward").unwrap();
+
+                match call.try_as_basic_value().left() {
+                    Some(value) => self.builder.build_return(Some(&value)).unwrap(),
+                    None => self.builder.build_return(None).unwrap(),
+                };
+
+                if let Some(block) = saved_block {
+                    self.builder.position_at_end(block);
+                }
+
+                thunk
+            }
+        };
+
+        let null_env = self.llvm_ctx.ptr_type(AddressSpace::default()).const_null();
+
+        self.build_closure_value(thunk, null_env)
+    }
+
+    fn generate_closure_fn(&mut self, 
+        closure_ty: &LangType, 
+        args: &'llvm [(&'llvm str, hir::HirId, hir::HirTyHint<'llvm>)], 
+        body: &'llvm HirExpr, 
+        captures: &[Capture<'llvm>], 
+        env_ty: StructType<'llvm>
+    ) -> FunctionValue<'llvm> {
+        let LangType::Function { params, ret } = closure_ty else {
+            panic!("closure has non function type {:?}", closure_ty);
+        };
+
+        let name = format!("{}$closure{}", self.global_ctx.module_name, self.closure_count);
+        self.closure_count += 1;
+
+        let fn_type = self.translate_to_fn_type(params, ret, true);
+        let function = self.llvm_mod.add_function(&name, fn_type, Some(Linkage::Internal));
+
+        let saved_block = self.builder.get_insert_block();
+        let saved_env = std::mem::replace(&mut self.env_variables, VariableEnv::new());
+
+        let entry = self.llvm_ctx.append_basic_block(function, "start");
+        self.builder.position_at_end(entry);
+
+        let env_ptr = function.get_nth_param(0).unwrap().into_pointer_value();
+
+        for (i, capture) in captures.iter().enumerate() {
+            let field_ty = env_ty.get_field_type_at_index(i as u32).unwrap();
+            let field = self.builder.build_struct_gep(env_ty, env_ptr, i as u32, capture.name).unwrap();
+            let value = self.builder.build_load(field_ty, field, capture.name).unwrap();
+
+            let alloca = self.builder.build_alloca(field_ty, capture.name).unwrap();
+            self.builder.build_store(alloca, value).unwrap();
+
+            self.env_variables.declare_variable(capture.name, PtrValue {
+                ptr: alloca,
+                value_type: field_ty
+            }).unwrap();
+        }
+
+        let arg_names: Vec<&'llvm str> = args.iter().map(|arg| arg.0).collect();
+        self.bind_arguments(function, &arg_names, 1);
+
+        let value = self.generate_inner_decls_ir(body);
+
+        if self.builder.get_insert_block().unwrap().get_terminator().is_none() {
+            if ret.is_unit() {
+                self.builder.build_return(None).unwrap();
+            } else {
+                self.builder.build_return(Some(&value)).unwrap();
+            }
+        }
+
+        self.env_variables = saved_env;
+
+        if let Some(block) = saved_block {
+            self.builder.position_at_end(block);
+        }
+
+        function
+    }
+
+    fn build_indirect_call(&mut self, 
+        node: &HirExpr, 
+        name: &str, 
+        callee: PtrValue<'llvm>, 
+        args: &'llvm [HirExpr]
+    ) -> BasicValueEnum<'llvm> {
+        let callee_id = self.global_ctx.module_symbols.borrow().get_pair(&node.id).unwrap().id;
+        let callee_ty = self.type_of(&callee_id);
+
+        let LangType::Function { params, ret } = callee_ty else {
+            panic!("{} has type {:?} and can not be called", name, callee_ty);
+        };
+
+        let closure = self.builder.build_load(callee.value_type, callee.ptr, name).unwrap().into_struct_value();
+
+        let fn_ptr = self.builder.build_extract_value(closure, 0, "closure_fn").unwrap().into_pointer_value();
+        let env_ptr = self.builder.build_extract_value(closure, 1, "closure_env").unwrap();
+
+        let mut llvm_args: Vec<BasicMetadataValueEnum<'llvm>> = vec![env_ptr.into()];
+
+        for arg in args {
+            llvm_args.push(self.generate_inner_decls_ir(arg).into());
+        }
+
+        let fn_type = self.translate_to_fn_type(&params, &ret, true);
+
+        let call = self.builder.build_indirect_call(fn_type, fn_ptr, llvm_args.as_slice(), name).unwrap();
+
+        match call.try_as_basic_value().left() {
+            Some(value) => value,
+            None => self.default_val(),
+        }
+    }
+
+    fn build_dyn_value(&mut self, value: BasicValueEnum<'llvm>, source_ty: &LangType, trait_name: &str) -> BasicValueEnum<'llvm> {
+        let LangType::Struct(ty_name) = source_ty else {
+            panic!("value of type {:?} can not be used as dyn {}", source_ty, trait_name);
+        };
+
+        // the value is boxed, so the object stays valid independently of the current frame
+        // TODO: the box leaks. Dyn values are copied by value and escape through returns and fields, so it can
+        // only be freed once dyn values have a single owner, not at the exit of the scope that created it
+        let data_ptr = self.builder.build_malloc(value.get_type(), "dyn_data").unwrap();
+        self.builder.build_store(data_ptr, value).unwrap();
+
+        let vtable = self.vtable_for(ty_name, trait_name);
+
+        let object = dyn_struct_type(self.llvm_ctx).get_undef();
+
+        let object = self.builder.build_insert_value(object, data_ptr, 0, "dyn_data").unwrap()
+            .into_struct_value();
+
+        self.builder.build_insert_value(object, vtable, 1, "dyn_vtable").unwrap()
+            .into_struct_value()
+            .as_basic_value_enum()
+    }
+
+    fn vtable_type(&self, method_count: usize) -> StructType<'llvm> {
+        let ptr_type = self.llvm_ctx.ptr_type(AddressSpace::default());
+        let fields: Vec<BasicTypeEnum<'llvm>> = vec![ptr_type.into(); method_count];
+
+        self.llvm_ctx.struct_type(&fields, false)
+    }
+
+    // one constant global per implemented (type, trait) pair, holding the methods in trait order
+    fn vtable_for(&mut self, ty_name: &str, trait_name: &str) -> PointerValue<'llvm> {
+        let vtable_name = format!("{}.{}$vtable", ty_name, trait_name);
+
+        if let Some(global) = self.llvm_mod.get_global(&vtable_name) {
+            return global.as_pointer_value();
+        }
+
+        let info = self.global_ctx.module_traits.borrow().get(trait_name).cloned().unwrap();
+
+        let entries: Vec<BasicValueEnum<'llvm>> = info.methods.iter()
+            .map(|method| {
+                let shim = self.build_dyn_shim(ty_name, method.name);
+                shim.as_global_value().as_pointer_value().as_basic_value_enum()
+            })
+            .collect();
+
+        let vtable_ty = self.vtable_type(entries.len());
+
+        let global = self.llvm_mod.add_global(vtable_ty, None, &vtable_name);
+        global.set_initializer(&vtable_ty.const_named_struct(&entries));
+        global.set_constant(true);
+        global.set_linkage(Linkage::Internal);
+
+        global.as_pointer_value()
+    }
+
+    // vtable entries receive the boxed value, the shim unboxes it and forwards to the method
+    fn build_dyn_shim(&mut self, ty_name: &str, method: &str) -> FunctionValue<'llvm> {
+        let shim_name = format!("{}.{}$dyn", ty_name, method);
+
+        if let Some(shim) = self.func_env.get(&shim_name) {
+            return *shim;
+        }
+
+        let method_id = self.global_ctx.module_methods.borrow().get_method(ty_name, method).unwrap().id;
+        let method_ty = self.type_of(&method_id);
+
+        let LangType::Function { params, ret } = method_ty else {
+            panic!("method {} has non function type {:?}", method, method_ty);
+        };
+
+        let symbol = self.fn_symbols[&method_id].clone();
+        let target = self.translate_to_function_sig(&symbol, &params, false, &ret);
+
+        let shim_type = self.translate_to_fn_type(&params[1..], &ret, true);
+        let shim = self.llvm_mod.add_function(&shim_name, shim_type, Some(Linkage::Internal));
+        self.func_env.insert(shim_name, shim);
+
+        let saved_block = self.builder.get_insert_block();
+
+        let entry = self.llvm_ctx.append_basic_block(shim, "start");
+        self.builder.position_at_end(entry);
+
+        let data_ptr = shim.get_nth_param(0).unwrap().into_pointer_value();
+        let receiver = self.builder.build_load(translate_to_llvm_ty(self.llvm_ctx, &params[0]), data_ptr, "self").unwrap();
+
+        let mut forwarded: Vec<BasicMetadataValueEnum<'llvm>> = vec![receiver.into()];
+        forwarded.extend(shim.get_param_iter().skip(1).map(|param| -> BasicMetadataValueEnum<'llvm> { param.into() }));
+
+        let call = self.builder.build_call(target, &forwarded, "forward").unwrap();
+
+        match call.try_as_basic_value().left() {
+            Some(value) => self.builder.build_return(Some(&value)).unwrap(),
+            None => self.builder.build_return(None).unwrap(),
+        };
+
+        if let Some(block) = saved_block {
+            self.builder.position_at_end(block);
+        }
+
+        shim
+    }
+
+    fn build_dyn_call(&mut self, 
+        receiver: &'llvm HirExpr, 
+        trait_name: &str, 
+        method: &str, 
+        args: &'llvm [HirExpr]
+    ) -> BasicValueEnum<'llvm> {
+        let info = self.global_ctx.module_traits.borrow().get(trait_name).cloned().unwrap();
+
+        let index = info.methods.iter().position(|entry| entry.name == method).unwrap();
+
+        let LangType::Function { params, ret } = &info.methods[index].ty else {
+            panic!("method {} has non function type {:?}", method, info.methods[index].ty);
+        };
+
+        let object = self.generate_inner_decls_ir(receiver).into_struct_value();
+
+        let data_ptr = self.builder.build_extract_value(object, 0, "dyn_data").unwrap();
+        let vtable_ptr = self.builder.build_extract_value(object, 1, "dyn_vtable").unwrap().into_pointer_value();
+
+        let vtable_ty = self.vtable_type(info.methods.len());
+        let entry = self.builder.build_struct_gep(vtable_ty, vtable_ptr, index as u32, "vtable_entry").unwrap();
+        let fn_ptr = self.builder.build_load(self.llvm_ctx.ptr_type(AddressSpace::default()), entry, method).unwrap()
+            .into_pointer_value();
+
+        let mut llvm_args: Vec<BasicMetadataValueEnum<'llvm>> = vec![data_ptr.into()];
+
+        for arg in args {
+            llvm_args.push(self.generate_inner_decls_ir(arg).into());
+        }
+
+        let fn_type = self.translate_to_fn_type(&params[1..], ret, true);
+
+        let call = self.builder.build_indirect_call(fn_type, fn_ptr, llvm_args.as_slice(), method).unwrap();
+
+        match call.try_as_basic_value().left() {
+            Some(value) => value,
+            None => self.default_val(),
+        }
+    }
+
+    fn lookup_const(&self, node: &HirExpr) -> Option<ConstValue> {
+        let symbol = self.global_ctx.module_symbols.borrow().get_pair(&node.id).cloned()?;
+
+        self.global_ctx.module_consts.borrow().get_value(&symbol.id).cloned()
+    }
+
+    fn translate_const_value(&self, value: &ConstValue, ty: &LangType) -> BasicValueEnum<'llvm> {
+        let llvm_ty = translate_to_llvm_ty(self.llvm_ctx, ty);
+
+        match value {
+            ConstValue::Int(val) => llvm_ty.into_int_type().const_int(*val as u64, true).as_basic_value_enum(),
+            ConstValue::Float(val) => llvm_ty.into_float_type().const_float(*val).as_basic_value_enum(),
+            ConstValue::Bool(val) => llvm_ty.into_int_type().const_int(*val as u64, false).as_basic_value_enum(),
+            ConstValue::Char(val) => llvm_ty.into_int_type().const_int(*val as u64, false).as_basic_value_enum(),
+        }
+    }
+
+    fn default_val(&self) -> BasicValueEnum<'llvm> {
+        BasicValueEnum::IntValue(self.llvm_ctx.i64_type().const_zero())
+    }
+
+    fn generate_function(&mut self, item: &'llvm HirModuleItem) {
+        let HirModuleItem::Func { id, generics, visibility, .. } = item else {
+            return;
+        };
+
+        // generic functions are only emitted per instance, on their first call
+        if !generics.is_empty() {
+            return;
+        }
+
+        let is_global = match visibility {
+            HirVisibility::Public => true,
+            HirVisibility::Private => false
+        };
+
+        let symbol = self.fn_symbols[id].clone();
+
+        self.emit_function(item, &symbol, is_global);
+    }
+
+    fn instantiate(&mut self, callee: HirId, symbol: &str, type_args: BTreeMap<String, LangType>) {
+        if self.func_env.contains_key(symbol) {
+            return;
+        }
+
+        let item = self.fn_items[&callee];
+
+        let saved_block = self.builder.get_insert_block();
+        let saved_env = std::mem::replace(&mut self.env_variables, VariableEnv::new());
+        let saved_args = std::mem::replace(&mut self.type_args, type_args);
+
+        self.emit_function(item, symbol, false);
+
+        self.type_args = saved_args;
+        self.env_variables = saved_env;
+
+        if let Some(block) = saved_block {
+            self.builder.position_at_end(block);
+        }
+    }
+
+    fn emit_function(&mut self, item: &'llvm HirModuleItem, symbol: &str, is_global: bool) {
+        let HirModuleItem::Func { id, name, args, patterns, body, .. } = item else {
+            return;
+        };
+
+        let fn_ty = self.type_of(id);
+
+        let LangType::Function { params: arg_types, ret: ret_ty } = fn_ty else {
+            panic!("function {} has non function type {:?}", name, fn_ty);
+        };
+
+        let signature = self.translate_to_function_sig(
+            symbol, 
+            &arg_types, 
+            is_global, 
+            &ret_ty
+        );
+
+        let llvm_block = self.llvm_ctx.append_basic_block(signature, "start");
+        self.builder.position_at_end(llvm_block);
+
+        self.env_variables.push_scope();
+
+        let arg_names: Vec<&'llvm str> = args.iter().map(|arg| arg.0).collect();
+        self.bind_arguments(signature, &arg_names, 0);
+
+        for (index, pattern) in patterns {
+            let param = signature.get_nth_param(*index as u32).unwrap();
+            self.bind_pattern(pattern, param);
+        }
+
+        let value = self.generate_inner_decls_ir(&body);
+
+        self.env_variables.pop_scope();
+
+        let current_block = self.builder.get_insert_block().unwrap();
+
+        if current_block.get_terminator().is_none() {
+            if ret_ty.is_unit() {
+                self.builder.build_return(None).unwrap();
+            } else {
+                self.builder.build_return(Some(&value)).unwrap();
+            }
+        }
+    }
+
+    fn generate_ir(&mut self) {
+        self.declare_struct_types();
+
+        for file in &self.global_ctx.module_files {
+            for decl in &file.items {
+                match decl {
+                    HirModuleItem::Func { .. } => self.generate_function(decl),
+
+                    HirModuleItem::Struct { .. } | HirModuleItem::Trait { .. } | HirModuleItem::TypeAlias { .. } => (),
+
+                    HirModuleItem::Impl { methods, .. } => {
+                        for method in methods {
+                            self.generate_function(method);
+                        }
+                    }
+
+                    HirModuleItem::Const { id, name, visibility, .. } => {
+                        // private constants are only inlined at their use sites
+                        if matches!(visibility, HirVisibility::Private) {
+                            continue;
+                        }
+
+                        let value = self.global_ctx.module_consts.borrow().get_value(id).cloned().unwrap();
+                        let ty = self.type_of(id);
+                        let llvm_value = self.translate_const_value(&value, &ty);
+
+                        let global = self.llvm_mod.add_global(llvm_value.get_type(), None, &self.global_ctx.mangled_name(name));
+                        global.set_initializer(&llvm_value);
+                        global.set_constant(true);
+                        global.set_linkage(Linkage::External);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// methods are emitted under type qualified names, so `Point.len` can not clash with a free `len`,
+// and all of them under the module path
+fn collect_fn_symbols(ctx: &GlobalCtx) -> HashMap<HirId, String> {
+    let mut symbols = HashMap::new();
+
+    for (id, function) in ctx.module_imports.borrow().iter() {
+        symbols.insert(*id, function.symbol.clone());
+    }
+
+    for file in &ctx.module_files {
+        for item in &file.items {
+            match item {
+                HirModuleItem::Func { id, name, .. } => {
+                    symbols.insert(*id, ctx.mangled_name(name));
+                }
+
+                HirModuleItem::Impl { target, methods, .. } => {
+                    for method in methods {
+                        if let HirModuleItem::Func { id, name, .. } = method {
+                            symbols.insert(*id, ctx.mangled_name(&format!("{}.{}", target, name)));
+                        }
+                    }
+                }
+
+                _ => ()
+            }
+        }
+    }
+
+    symbols
+}
+
+fn collect_fn_items<'a>(ctx: &'a GlobalCtx<'a>) -> HashMap<HirId, &'a HirModuleItem<'a>> {
+    let mut items = HashMap::new();
+
+    for file in &ctx.module_files {
+        for item in &file.items {
+            match item {
+                HirModuleItem::Func { id, .. } => {
+                    items.insert(*id, item);
+                }
+
+                HirModuleItem::Impl { methods, .. } => {
+                    for method in methods {
+                        if let HirModuleItem::Func { id, .. } = method {
+                            items.insert(*id, method);
+                        }
+                    }
+                }
+
+                _ => ()
+            }
+        }
+    }
+
+    items
+}
+
+fn instance_symbol(symbol: &str, type_args: &BTreeMap<String, LangType>) -> String {
+    let args: Vec<_> = type_args.values().map(|ty| ty.short_text()).collect();
+
+    format!("{}<{}>", symbol, args.join(","))
+}
+
+pub fn generate_object_code<'a, 'b>(ctx: &'a GlobalCtx<'a>) -> Vec<u8> {
+    let llvm_ctx = Context::create();
+
+    Target::initialize_all(&InitializationConfig::default());
+
+    let triple = TargetMachine::get_default_triple();
+    let cpu_features = TargetMachine::get_host_cpu_features();
+    let cpu_name = TargetMachine::get_host_cpu_name();
+    let target = Target::from_triple(&triple).unwrap();
+
+    let machine = target
+        .create_target_machine(
+            &triple,
+            cpu_name.to_str().unwrap(),
+            cpu_features.to_str().unwrap(),
             inkwell::OptimizationLevel::Aggressive,
             inkwell::targets::RelocMode::PIC,
             inkwell::targets::CodeModel::Default,
@@ -396,4 +1660,7 @@ pub fn generate_object_code<'a, 'b>(ctx: &'a GlobalCtx<'a>) -> Vec<u8> {
     ).unwrap();
 
     mem_buf.as_slice().to_vec()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests;
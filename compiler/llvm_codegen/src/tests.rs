@@ -0,0 +1,73 @@
+use ast_lowering::translate_to_hir;
+use const_eval::evaluate_consts;
+use hir_resolver::resolve_module;
+use inkwell::{context::Context, targets::{InitializationConfig, Target}, OptimizationLevel};
+use middle::{BuildType, GlobalCtx};
+use parser::parse_file;
+use type_checker::validate_hir;
+use types_lowering::type_hir_module;
+
+use crate::ModuleCodeGenerator;
+
+// compiles the source and runs its `main` in a JIT
+fn run_main(source: &str) -> i64 {
+    let parsed = parse_file("test", source);
+
+    let mut ctx = GlobalCtx::new("test".to_string(), "x86_64".to_string(), BuildType::Executable);
+    let file = translate_to_hir(&ctx, &parsed);
+    ctx.module_files.push(file);
+
+    resolve_module(&mut ctx);
+    type_hir_module(&mut ctx);
+    validate_hir(&ctx);
+    evaluate_consts(&ctx);
+
+    Target::initialize_native(&InitializationConfig::default()).unwrap();
+
+    let llvm_ctx = Context::create();
+    let mut module_gen = ModuleCodeGenerator::new(&llvm_ctx, &ctx);
+    module_gen.generate_ir();
+
+    let engine = module_gen.llvm_mod.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+
+    unsafe {
+        engine.get_function::<unsafe extern "C" fn() -> i64>("main").unwrap().call()
+    }
+}
+
+#[test]
+fn copies_of_a_vector_share_its_buffer() {
+    let source = "
+        fn main() -> Int {
+            val a: Vec<Int> = Vec::new();
+            val b = a;
+            b.push(1);
+            b.push(2);
+            b.push(3);
+            b.push(4);
+            b.push(5);
+            a.push(6);
+            ret a.len() * 100 + b.len() * 10 + a[5] - b[4];
+        }
+    ";
+
+    assert_eq!(run_main(source), 661);
+}
+
+#[test]
+fn slices_view_arrays_and_vectors() {
+    let source = "
+        fn last(values: [Int]) -> Int {
+            ret values[values.len() - 1];
+        }
+
+        fn main() -> Int {
+            val array = [1, 2, 3];
+            val v: Vec<Int> = Vec::new();
+            v.push(40);
+            ret last(array) + last([10, 20]) + last(v);
+        }
+    ";
+
+    assert_eq!(run_main(source), 63);
+}
@@ -1,6 +1,6 @@
-use std::{cell::RefCell, collections::{hash_map::{Entry, Iter}, HashMap}};
+use std::{cell::RefCell, collections::{hash_map::{Entry, Iter}, BTreeMap, HashMap, HashSet}, fmt};
 
-use hir::{HirFile, HirId};
+use hir::{HirFile, HirId, HirModuleItem, HirVisibility};
 
 pub mod ty;
 
@@ -12,11 +12,26 @@ pub enum BuildType {
 pub struct GlobalCtx<'a> {
     pub module_name: String,
     pub module_ty_info: RefCell<HirModuleTypeTable>,
+    pub module_consts: RefCell<HirModuleConstTable>,
     pub module_symbols: RefCell<NamePairs>,
+    pub module_captures: RefCell<ClosureCaptures<'a>>,
+    pub module_structs: RefCell<StructTable<'a>>,
+    pub module_methods: RefCell<MethodTable<'a>>,
+    pub module_traits: RefCell<TraitTable<'a>>,
+    pub module_type_aliases: RefCell<TypeAliasTable<'a>>,
+    pub module_instances: RefCell<GenericInstances>,
+    pub module_coercions: RefCell<Coercions>,
+    pub module_return_inference: RefCell<ReturnInference>,
+    pub module_imports: RefCell<ImportedFunctions>,
+    pub module_interfaces: Vec<ModuleInterface>,
+    pub module_warnings: RefCell<Vec<String>>,
+    pub module_errors: RefCell<Vec<String>>,
     pub module_exports: Vec<(&'a str, HirId)>,
     pub module_files: Vec<HirFile<'a>>,
     pub arch: String,
-    pub build_type: BuildType
+    pub build_type: BuildType,
+    // opt-in, without it a function that has no return type written down returns Unit
+    pub infer_return_types: bool
 }
 
 impl<'a> GlobalCtx<'a> {
@@ -24,19 +39,231 @@ impl<'a> GlobalCtx<'a> {
         Self {
             module_name,
             module_ty_info: RefCell::new(HirModuleTypeTable::new()),
+            module_consts: RefCell::new(HirModuleConstTable::new()),
             module_exports: Vec::new(),
             arch,
             module_symbols: RefCell::new(NamePairs::new()),
+            module_captures: RefCell::new(ClosureCaptures::new()),
+            module_structs: RefCell::new(StructTable::new()),
+            module_methods: RefCell::new(MethodTable::new()),
+            module_traits: RefCell::new(TraitTable::new()),
+            module_type_aliases: RefCell::new(TypeAliasTable::new()),
+            module_instances: RefCell::new(GenericInstances::new()),
+            module_coercions: RefCell::new(Coercions::new()),
+            module_return_inference: RefCell::new(ReturnInference::new()),
+            module_imports: RefCell::new(ImportedFunctions::new()),
+            module_interfaces: Vec::new(),
+            module_warnings: RefCell::new(Vec::new()),
+            module_errors: RefCell::new(Vec::new()),
             module_files: Vec::new(),
-            build_type
+            build_type,
+            infer_return_types: false
+        }
+    }
+
+    // object symbols carry the full module path, like `net.http::get`, so equally named
+    // items of different modules do not collide when linked together
+    pub fn mangled_name(&self, name: &str) -> String {
+        match self.build_type {
+            BuildType::Executable if name == "main" => name.to_string(),
+            _ => mangle(&self.module_name, name)
+        }
+    }
+
+    pub fn warn(&self, message: String) {
+        self.module_warnings.borrow_mut().push(message);
+    }
+
+    // type errors are collected so that one run reports all of them
+    pub fn error(&self, message: String) {
+        self.module_errors.borrow_mut().push(message);
+    }
+
+    pub fn abort_on_errors(&self) {
+        let errors = self.module_errors.borrow();
+
+        if errors.is_empty() {
+            return;
+        }
+
+        for error in errors.iter() {
+            eprintln!("error: {}", error);
+        }
+
+        std::process::exit(1);
+    }
+
+    // what importing modules get to see of this one, private functions included so
+    // that calling them can be reported as a visibility error instead of an unknown name
+    pub fn interface(&self) -> ModuleInterface {
+        let mut functions = Vec::new();
+        let mut consts = Vec::new();
+        let mut traits = Vec::new();
+        let mut public_structs = HashSet::new();
+        let mut private_traits = HashSet::new();
+
+        for file in &self.module_files {
+            for item in &file.items {
+                match item {
+                    HirModuleItem::Func { id, name, visibility, .. } => functions.push(ModuleFunction {
+                        name: name.to_string(),
+                        ty: self.module_ty_info.borrow().get_type(id).unwrap().ty.clone(),
+                        public: matches!(visibility, HirVisibility::Public)
+                    }),
+
+                    // constants are folded before the interface is taken, importers get the value itself
+                    HirModuleItem::Const { id, name, visibility, .. } => {
+                        let Some(value) = self.module_consts.borrow().get_value(id).copied() else {
+                            continue;
+                        };
+
+                        consts.push(ModuleConst {
+                            name: name.to_string(),
+                            ty: self.module_ty_info.borrow().get_type(id).unwrap().ty.clone(),
+                            value,
+                            public: matches!(visibility, HirVisibility::Public)
+                        });
+                    }
+
+                    HirModuleItem::Trait { name, visibility: HirVisibility::Public, .. } => {
+                        let methods = self.module_traits.borrow().get(name).unwrap().methods.iter()
+                            .map(|method| (method.name.to_string(), method.ty.clone()))
+                            .collect();
+
+                        traits.push(ModuleTrait { name: name.to_string(), methods });
+                    }
+
+                    HirModuleItem::Trait { name, .. } => {
+                        private_traits.insert(*name);
+                    }
+
+                    HirModuleItem::Struct { name, visibility: HirVisibility::Public, .. } => {
+                        public_structs.insert(*name);
+                    }
+
+                    _ => {}
+                }
+            }
+        }
+
+        // impls of imported traits are exported too, as long as their type is public
+        let mut impls: Vec<(String, String)> = self.module_traits.borrow().impls()
+            .filter(|(target, trait_name)| public_structs.contains(target) && !private_traits.contains(trait_name))
+            .map(|(target, trait_name)| (target.to_string(), trait_name.to_string()))
+            .collect();
+
+        impls.sort();
+
+        ModuleInterface {
+            name: self.module_name.clone(),
+            functions,
+            consts,
+            traits,
+            impls
         }
     }
 }
 
+pub fn mangle(module_name: &str, name: &str) -> String {
+    format!("{}::{}", module_name, name)
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleFunction {
+    pub name: String,
+    pub ty: ty::LangType,
+    pub public: bool
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleConst {
+    pub name: String,
+    pub ty: ty::LangType,
+    pub value: ConstValue,
+    pub public: bool
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleTrait {
+    pub name: String,
+    pub methods: Vec<(String, ty::LangType)>
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleInterface {
+    // full module path, like `net.http`
+    pub name: String,
+    pub functions: Vec<ModuleFunction>,
+    pub consts: Vec<ModuleConst>,
+    // only pub traits, and the impls of pub structs as (type, trait) pairs
+    pub traits: Vec<ModuleTrait>,
+    pub impls: Vec<(String, String)>
+}
+
+impl ModuleInterface {
+    pub fn function(&self, name: &str) -> Option<&ModuleFunction> {
+        self.functions.iter().find(|function| function.name == name)
+    }
+
+    pub fn constant(&self, name: &str) -> Option<&ModuleConst> {
+        self.consts.iter().find(|constant| constant.name == name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportedFunction {
+    pub symbol: String,
+    pub ty: ty::LangType
+}
+
+// functions of other modules called from this one, each gets an id of this module on first use
+#[derive(Default)]
+pub struct ImportedFunctions {
+    functions: HashMap<HirId, ImportedFunction>
+}
+
+impl ImportedFunctions {
+    pub fn new() -> ImportedFunctions {
+        ImportedFunctions {
+            functions: HashMap::new()
+        }
+    }
+
+    pub fn import(&mut self, symbol: String, ty: ty::LangType) -> HirId {
+        if let Some((id, _)) = self.functions.iter().find(|(_, function)| function.symbol == symbol) {
+            return *id;
+        }
+
+        let id = HirId::new();
+        self.functions.insert(id, ImportedFunction { symbol, ty });
+        id
+    }
+
+    pub fn iter(&self) -> Iter<'_, HirId, ImportedFunction> {
+        self.functions.iter()
+    }
+}
+
+// what a name was bound by, imports being the functions of other modules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Function,
+    Constant,
+    Parameter,
+    Local,
+    Import
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct SymbolInfo {
     pub id: HirId,
-    pub is_external_name: bool
+    pub kind: SymbolKind
+}
+
+impl SymbolInfo {
+    pub fn is_external_name(&self) -> bool {
+        self.kind == SymbolKind::Import
+    }
 }
 
 
@@ -59,11 +286,336 @@ impl NamePairs {
         self.pairs.get(hir_id)
     }
 
+    pub fn is_referenced(&self, id: &HirId) -> bool {
+        self.pairs.values().any(|symbol| symbol.id == *id)
+    }
+
     pub fn iter(&self) -> Iter<'_, HirId, SymbolInfo> {
         self.pairs.iter()
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct Capture<'a> {
+    pub name: &'a str,
+    pub id: HirId
+}
+
+#[derive(Default)]
+pub struct ClosureCaptures<'a> {
+    captures: HashMap<HirId, Vec<Capture<'a>>>,
+}
+
+impl<'a> ClosureCaptures<'a> {
+    pub fn new() -> ClosureCaptures<'a> {
+        ClosureCaptures {
+            captures: HashMap::new()
+        }
+    }
+
+    pub fn add_capture(&mut self, closure_id: HirId, capture: Capture<'a>) {
+        let captures = self.captures.entry(closure_id).or_default();
+
+        if !captures.iter().any(|known| known.id == capture.id) {
+            captures.push(capture);
+        }
+    }
+
+    pub fn get_captures(&self, closure_id: &HirId) -> &[Capture<'a>] {
+        self.captures.get(closure_id).map(|captures| captures.as_slice()).unwrap_or(&[])
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructInfo<'a> {
+    pub id: HirId,
+    pub fields: Vec<(&'a str, ty::LangType)>
+}
+
+impl<'a> StructInfo<'a> {
+    pub fn field(&self, name: &str) -> Option<(usize, &ty::LangType)> {
+        self.fields.iter()
+            .position(|field| field.0 == name)
+            .map(|index| (index, &self.fields[index].1))
+    }
+}
+
+#[derive(Default)]
+pub struct StructTable<'a> {
+    structs: HashMap<&'a str, StructInfo<'a>>
+}
+
+impl<'a> StructTable<'a> {
+    pub fn new() -> StructTable<'a> {
+        StructTable {
+            structs: HashMap::new()
+        }
+    }
+
+    pub fn declare(&mut self, name: &'a str, id: HirId) -> bool {
+        match self.structs.entry(name) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(StructInfo { id, fields: Vec::new() });
+                true
+            }
+        }
+    }
+
+    pub fn set_fields(&mut self, name: &str, fields: Vec<(&'a str, ty::LangType)>) {
+        self.structs.get_mut(name).unwrap().fields = fields;
+    }
+
+    pub fn get(&self, name: &str) -> Option<&StructInfo<'a>> {
+        self.structs.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.structs.contains_key(name)
+    }
+
+    pub fn iter(&self) -> Iter<'_, &'a str, StructInfo<'a>> {
+        self.structs.iter()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MethodInfo {
+    pub id: HirId,
+    pub has_self: bool
+}
+
+// methods and associated functions, keyed by the implemented type and their name
+#[derive(Default)]
+pub struct MethodTable<'a> {
+    methods: HashMap<(&'a str, &'a str), MethodInfo>
+}
+
+impl<'a> MethodTable<'a> {
+    pub fn new() -> MethodTable<'a> {
+        MethodTable {
+            methods: HashMap::new()
+        }
+    }
+
+    pub fn add_method(&mut self, target: &'a str, name: &'a str, info: MethodInfo) -> bool {
+        self.methods.insert((target, name), info).is_none()
+    }
+
+    pub fn get_method(&self, target: &str, name: &str) -> Option<MethodInfo> {
+        self.methods.get(&(target, name)).copied()
+    }
+
+    pub fn names_of<'b>(&'b self, target: &'b str) -> impl Iterator<Item = &'a str> + 'b {
+        self.methods.keys()
+            .filter(move |(method_target, _)| *method_target == target)
+            .map(|(_, name)| *name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TraitMethodInfo<'a> {
+    pub name: &'a str,
+    pub ty: ty::LangType
+}
+
+#[derive(Debug, Clone)]
+pub struct TraitInfo<'a> {
+    pub id: HirId,
+    pub methods: Vec<TraitMethodInfo<'a>>
+}
+
+impl<'a> TraitInfo<'a> {
+    pub fn method(&self, name: &str) -> Option<&TraitMethodInfo<'a>> {
+        self.methods.iter().find(|method| method.name == name)
+    }
+}
+
+#[derive(Default)]
+pub struct TraitTable<'a> {
+    traits: HashMap<&'a str, TraitInfo<'a>>,
+    impls: HashSet<(&'a str, &'a str)>
+}
+
+impl<'a> TraitTable<'a> {
+    pub fn new() -> TraitTable<'a> {
+        TraitTable {
+            traits: HashMap::new(),
+            impls: HashSet::new()
+        }
+    }
+
+    pub fn declare(&mut self, name: &'a str, id: HirId) -> bool {
+        match self.traits.entry(name) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(TraitInfo { id, methods: Vec::new() });
+                true
+            }
+        }
+    }
+
+    pub fn set_methods(&mut self, name: &str, methods: Vec<TraitMethodInfo<'a>>) {
+        self.traits.get_mut(name).unwrap().methods = methods;
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TraitInfo<'a>> {
+        self.traits.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.traits.contains_key(name)
+    }
+
+    pub fn add_impl(&mut self, target: &'a str, trait_name: &'a str) -> bool {
+        self.impls.insert((target, trait_name))
+    }
+
+    pub fn implements(&self, target: &str, trait_name: &str) -> bool {
+        self.impls.contains(&(target, trait_name))
+    }
+
+    pub fn iter(&self) -> Iter<'_, &'a str, TraitInfo<'a>> {
+        self.traits.iter()
+    }
+
+    pub fn impls(&self) -> impl Iterator<Item = &(&'a str, &'a str)> {
+        self.impls.iter()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeAliasInfo {
+    pub id: HirId,
+    // a newtype is a distinct type, a plain alias is just another name of its type
+    pub distinct: bool,
+    pub ty: ty::LangType
+}
+
+#[derive(Default)]
+pub struct TypeAliasTable<'a> {
+    aliases: HashMap<&'a str, TypeAliasInfo>
+}
+
+impl<'a> TypeAliasTable<'a> {
+    pub fn new() -> TypeAliasTable<'a> {
+        TypeAliasTable {
+            aliases: HashMap::new()
+        }
+    }
+
+    pub fn declare(&mut self, name: &'a str, id: HirId, distinct: bool) -> bool {
+        match self.aliases.entry(name) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(TypeAliasInfo { id, distinct, ty: ty::LangType::UNRESOLVED });
+                true
+            }
+        }
+    }
+
+    pub fn set_type(&mut self, name: &str, ty: ty::LangType) {
+        self.aliases.get_mut(name).unwrap().ty = ty;
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TypeAliasInfo> {
+        self.aliases.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.aliases.contains_key(name)
+    }
+
+    pub fn iter(&self) -> Iter<'_, &'a str, TypeAliasInfo> {
+        self.aliases.iter()
+    }
+}
+
+// type arguments of calls to generic functions, keyed by the call expression
+#[derive(Default)]
+pub struct GenericInstances {
+    instances: HashMap<HirId, BTreeMap<String, ty::LangType>>
+}
+
+impl GenericInstances {
+    pub fn new() -> GenericInstances {
+        GenericInstances {
+            instances: HashMap::new()
+        }
+    }
+
+    pub fn add_instance(&mut self, call_id: HirId, args: BTreeMap<String, ty::LangType>) {
+        self.instances.insert(call_id, args);
+    }
+
+    pub fn get_instance(&self, call_id: &HirId) -> Option<&BTreeMap<String, ty::LangType>> {
+        self.instances.get(call_id)
+    }
+}
+
+// implicit conversions of expression values, e.g. a struct passed where a `dyn Trait` is expected
+#[derive(Default)]
+pub struct Coercions {
+    targets: HashMap<HirId, ty::LangType>
+}
+
+impl Coercions {
+    pub fn new() -> Coercions {
+        Coercions {
+            targets: HashMap::new()
+        }
+    }
+
+    pub fn add_coercion(&mut self, expr_id: HirId, target: ty::LangType) {
+        self.targets.insert(expr_id, target);
+    }
+
+    pub fn get_coercion(&self, expr_id: &HirId) -> Option<&ty::LangType> {
+        self.targets.get(expr_id)
+    }
+
+    pub fn remove_coercion(&mut self, expr_id: &HirId) {
+        self.targets.remove(expr_id);
+    }
+}
+
+// private functions written without a return type, typed from their bodies when first needed
+#[derive(Default)]
+pub struct ReturnInference {
+    pending: HashSet<HirId>,
+    in_progress: HashSet<HirId>
+}
+
+impl ReturnInference {
+    pub fn new() -> ReturnInference {
+        ReturnInference {
+            pending: HashSet::new(),
+            in_progress: HashSet::new()
+        }
+    }
+
+    pub fn add_pending(&mut self, id: HirId) {
+        self.pending.insert(id);
+    }
+
+    pub fn start(&mut self, id: HirId) -> bool {
+        if !self.pending.remove(&id) {
+            return false;
+        }
+
+        self.in_progress.insert(id);
+        true
+    }
+
+    pub fn finish(&mut self, id: HirId) {
+        self.in_progress.remove(&id);
+    }
+
+    pub fn is_in_progress(&self, id: &HirId) -> bool {
+        self.in_progress.contains(id)
+    }
+}
+
 #[derive(Clone)]
 pub struct TypeInfo {
     pub ty: ty::LangType,
@@ -103,3 +655,46 @@ impl HirModuleTypeTable {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Char(char)
+}
+
+impl fmt::Display for ConstValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstValue::Int(val) => write!(f, "{}", val),
+            ConstValue::Float(val) => write!(f, "{}", val),
+            ConstValue::Bool(val) => write!(f, "{}", val),
+            ConstValue::Char(val) => write!(f, "{:?}", val)
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct HirModuleConstTable {
+    values: HashMap<HirId, ConstValue>
+}
+
+impl HirModuleConstTable {
+    pub fn new() -> HirModuleConstTable {
+        HirModuleConstTable {
+            values: HashMap::new()
+        }
+    }
+
+    pub fn insert_value(&mut self, hir_id: HirId, value: ConstValue) {
+        self.values.insert(hir_id, value);
+    }
+
+    pub fn get_value(&self, hir_id: &HirId) -> Option<&ConstValue> {
+        self.values.get(hir_id)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,72 @@
+use crate::ty::{LangType, Primitive};
+
+#[test]
+fn fits_integers_at_the_bounds_of_each_width() {
+    assert!(Primitive::I8.fits_integer(-128));
+    assert!(Primitive::I8.fits_integer(127));
+    assert!(!Primitive::I8.fits_integer(-129));
+    assert!(!Primitive::I8.fits_integer(128));
+
+    assert!(Primitive::U8.fits_integer(255));
+    assert!(!Primitive::U8.fits_integer(256));
+    assert!(!Primitive::U8.fits_integer(-1));
+
+    assert!(Primitive::Int.fits_integer(i64::MIN.into()));
+    assert!(!Primitive::Int.fits_integer(i128::from(i64::MAX) + 1));
+    assert!(Primitive::U64.fits_integer(u64::MAX.into()));
+
+    assert!(Primitive::I128.fits_integer(i128::MIN));
+    assert!(!Primitive::U128.fits_integer(-1));
+}
+
+#[test]
+fn fits_literals_by_magnitude_and_sign() {
+    assert!(Primitive::I8.fits_literal(128, true));
+    assert!(!Primitive::I8.fits_literal(128, false));
+    assert!(!Primitive::U8.fits_literal(1, true));
+    assert!(Primitive::U8.fits_literal(0, true));
+
+    assert!(Primitive::U128.fits_literal(u128::MAX, false));
+    assert!(!Primitive::I128.fits_literal(u128::MAX, false));
+    assert!(Primitive::I128.fits_literal(i128::MIN.unsigned_abs(), true));
+    assert!(!Primitive::I128.fits_literal(i128::MIN.unsigned_abs() + 1, true));
+    assert!(!Primitive::U128.fits_literal(i128::MIN.unsigned_abs(), true));
+}
+
+#[test]
+fn fits_floats_in_f32_up_to_its_largest_value() {
+    assert!(Primitive::F32.fits_float(f32::MAX as f64));
+    assert!(!Primitive::F32.fits_float(f64::MAX));
+    assert!(Primitive::F32.fits_float(f64::INFINITY));
+    assert!(Primitive::F64.fits_float(f64::MAX));
+}
+
+#[test]
+fn decodes_every_kind_of_type_it_encodes() {
+    let int = || Box::new(LangType::Primitives(Primitive::Int));
+
+    let types = [
+        LangType::Primitives(Primitive::U128),
+        LangType::StaticArray { size: 3, ty: int() },
+        LangType::Function { params: vec![LangType::Struct("Point".to_string()), *int()], ret: int() },
+        LangType::Param { name: "T".to_string(), bounds: vec!["Area".to_string(), "Show".to_string()] },
+        LangType::Dyn("Area".to_string()),
+        LangType::Tuple(vec![*int(), LangType::Slice(int())]),
+        LangType::Vec(Box::new(LangType::Option(int()))),
+        LangType::Result(int(), Box::new(LangType::Primitives(Primitive::Bool))),
+        LangType::Newtype { name: "Meters".to_string(), ty: Box::new(LangType::Primitives(Primitive::Float)) },
+    ];
+
+    for ty in types {
+        assert_eq!(LangType::from_bytes(&ty.to_bytes()), Some(ty));
+    }
+}
+
+#[test]
+fn rejects_truncated_types() {
+    let ty = LangType::Newtype { name: "Meters".to_string(), ty: Box::new(LangType::Primitives(Primitive::Float)) };
+    let bytes = ty.to_bytes();
+
+    assert_eq!(LangType::from_bytes(&bytes[..bytes.len() - 1]), None);
+    assert_eq!(LangType::from_bytes(&[]), None);
+}
@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::BTreeMap, fmt};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Primitive {
@@ -6,16 +6,51 @@ pub enum Primitive {
     Float,
     Char, 
     Bool,
-    Unit
+    Unit,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LangType {
     UNRESOLVED,
+    // the type of an expression whose error was already reported, it is accepted everywhere
+    // so that one mistake does not turn into a chain of follow-up errors
+    Error,
     Primitives(Primitive),
     StaticArray {
         size: u64,
         ty: Box<LangType>
+    },
+    Function {
+        params: Vec<LangType>,
+        ret: Box<LangType>
+    },
+    Struct(String),
+    Param {
+        name: String,
+        bounds: Vec<String>
+    },
+    Dyn(String),
+    Tuple(Vec<LangType>),
+    Slice(Box<LangType>),
+    Vec(Box<LangType>),
+    Option(Box<LangType>),
+    Result(Box<LangType>, Box<LangType>),
+    // declared with `newtype`, represented like the wrapped type but never mixed up with it
+    Newtype {
+        name: String,
+        ty: Box<LangType>
     }
 }
 
@@ -34,21 +69,136 @@ impl LangType {
         }
     }
 
+    pub fn is_function(&self) -> bool {
+        matches!(self, LangType::Function { .. })
+    }
+
+    pub fn is_struct(&self) -> bool {
+        matches!(self, LangType::Struct(_))
+    }
+
+    pub fn is_dyn(&self) -> bool {
+        matches!(self, LangType::Dyn(_))
+    }
+
+    pub fn is_tuple(&self) -> bool {
+        matches!(self, LangType::Tuple(_))
+    }
+
+    // the representation of a newtype, any other type is its own
+    pub fn underlying(&self) -> &LangType {
+        match self {
+            LangType::Newtype { ty, .. } => ty.underlying(),
+            _ => self
+        }
+    }
+
+    // element type of the values a `for` loop and indexing can walk over
+    pub fn element_type(&self) -> Option<&LangType> {
+        match self {
+            LangType::StaticArray { ty, .. } | LangType::Slice(ty) | LangType::Vec(ty) => Some(ty),
+            _ => None
+        }
+    }
+
+    // arrays, slices, vectors, options and results only have the methods the compiler provides
+    pub fn has_builtin_methods(&self) -> bool {
+        self.element_type().is_some() || matches!(self, LangType::Option(_) | LangType::Result(..))
+    }
+
+    // an unresolved part, like the element type of `None`, is filled in by the matching part of `other`
+    pub fn is_refined_by(&self, other: &LangType) -> bool {
+        match (self, other) {
+            (LangType::UNRESOLVED | LangType::Error, _) | (_, LangType::Error) => true,
+            (LangType::StaticArray { size, ty }, LangType::StaticArray { size: other_size, ty: other_ty }) =>
+                size == other_size && ty.is_refined_by(other_ty),
+            (LangType::Tuple(elems), LangType::Tuple(other_elems)) => elems.len() == other_elems.len()
+                && elems.iter().zip(other_elems.iter()).all(|(elem, other)| elem.is_refined_by(other)),
+            (LangType::Slice(ty), LangType::Slice(other_ty))
+            | (LangType::Vec(ty), LangType::Vec(other_ty))
+            | (LangType::Option(ty), LangType::Option(other_ty)) => ty.is_refined_by(other_ty),
+            (LangType::Result(ty, err), LangType::Result(other_ty, other_err)) =>
+                ty.is_refined_by(other_ty) && err.is_refined_by(other_err),
+            _ => self == other
+        }
+    }
+
+    pub fn has_error(&self) -> bool {
+        match self {
+            LangType::Error => true,
+            LangType::StaticArray { ty, .. } | LangType::Slice(ty) | LangType::Vec(ty) | LangType::Option(ty) => ty.has_error(),
+            LangType::Tuple(elems) => elems.iter().any(|elem| elem.has_error()),
+            LangType::Result(ty, err) => ty.has_error() || err.has_error(),
+            LangType::Function { params, ret } => params.iter().any(|param| param.has_error()) || ret.has_error(),
+            LangType::Newtype { ty, .. } => ty.has_error(),
+            _ => false
+        }
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        match self {
+            LangType::UNRESOLVED => false,
+            LangType::StaticArray { ty, .. } | LangType::Slice(ty) | LangType::Vec(ty) | LangType::Option(ty) => ty.is_resolved(),
+            LangType::Tuple(elems) => elems.iter().all(|elem| elem.is_resolved()),
+            LangType::Result(ty, err) => ty.is_resolved() && err.is_resolved(),
+            LangType::Function { params, ret } => params.iter().all(|param| param.is_resolved()) && ret.is_resolved(),
+            _ => true
+        }
+    }
+
+    pub fn is_param(&self) -> bool {
+        matches!(self, LangType::Param { .. })
+    }
+
+    // replaces generic parameters by the types they are instantiated with
+    pub fn substitute(&self, map: &BTreeMap<String, LangType>) -> LangType {
+        match self {
+            LangType::Param { name, .. } => map.get(name).cloned().unwrap_or_else(|| self.clone()),
+            LangType::StaticArray { size, ty } => LangType::StaticArray {
+                size: *size,
+                ty: Box::new(ty.substitute(map))
+            },
+            LangType::Function { params, ret } => LangType::Function {
+                params: params.iter().map(|param| param.substitute(map)).collect(),
+                ret: Box::new(ret.substitute(map))
+            },
+            LangType::Tuple(elems) => LangType::Tuple(elems.iter().map(|elem| elem.substitute(map)).collect()),
+            LangType::Slice(ty) => LangType::Slice(Box::new(ty.substitute(map))),
+            LangType::Vec(ty) => LangType::Vec(Box::new(ty.substitute(map))),
+            LangType::Option(ty) => LangType::Option(Box::new(ty.substitute(map))),
+            LangType::Result(ty, err) => LangType::Result(Box::new(ty.substitute(map)), Box::new(err.substitute(map))),
+            _ => self.clone()
+        }
+    }
+
     pub fn short_text(&self) -> Cow<str> {
         match self {
-            LangType::Primitives(Primitive::Int) => "i".into(),
-            LangType::Primitives(Primitive::Float) => "f".into(),
-            LangType::Primitives(Primitive::Char) => "c".into(),
-            LangType::Primitives(Primitive::Bool) => "b".into(),
-            LangType::Primitives(Primitive::Unit) => "u".into(),
+            LangType::Primitives(p) => p.short_name().into(),
             LangType::StaticArray{ty, size} => format!("[{};{}]", ty.short_text(), size).into(),
-            LangType::UNRESOLVED => "unresolved".into()
+            LangType::Function { params, ret } => {
+                let params: Vec<_> = params.iter().map(|param| param.short_text()).collect();
+                format!("fn({})->{}", params.join(","), ret.short_text()).into()
+            }
+            LangType::Struct(name) | LangType::Newtype { name, .. } => name.as_str().into(),
+            LangType::Param { name, .. } => name.as_str().into(),
+            LangType::Dyn(trait_name) => format!("dyn {}", trait_name).into(),
+            LangType::Tuple(elems) => {
+                let elems: Vec<_> = elems.iter().map(|elem| elem.short_text()).collect();
+                format!("({})", elems.join(",")).into()
+            }
+            LangType::Slice(ty) => format!("[{}]", ty.short_text()).into(),
+            LangType::Vec(ty) => format!("Vec<{}>", ty.short_text()).into(),
+            LangType::Option(ty) => format!("Option<{}>", ty.short_text()).into(),
+            LangType::Result(ty, err) => format!("Result<{},{}>", ty.short_text(), err.short_text()).into(),
+            LangType::UNRESOLVED => "unresolved".into(),
+            LangType::Error => "error".into()
         }
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
         match self {
-            LangType::UNRESOLVED => vec![0],
+            // an erroneous module is never exported
+            LangType::UNRESOLVED | LangType::Error => vec![0],
   
             LangType::Primitives(p) => {
                 let mut bytes = vec![2];
@@ -60,28 +210,246 @@ impl LangType {
                 bytes.extend_from_slice(ty.to_bytes().as_slice());
                 bytes.extend_from_slice(&size.to_le_bytes());
                 
+                bytes
+            }
+            LangType::Function { params, ret } => {
+                let mut bytes = vec![4, params.len() as u8];
+                for param in params {
+                    bytes.extend_from_slice(param.to_bytes().as_slice());
+                }
+                bytes.extend_from_slice(ret.to_bytes().as_slice());
+
+                bytes
+            }
+            LangType::Struct(name) => {
+                let mut bytes = vec![5];
+                bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+                bytes.extend_from_slice(name.as_bytes());
+
+                bytes
+            }
+            LangType::Param { name, bounds } => {
+                let mut bytes = vec![6];
+                bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+                bytes.extend_from_slice(name.as_bytes());
+                bytes.push(bounds.len() as u8);
+                for bound in bounds {
+                    bytes.extend_from_slice(&(bound.len() as u16).to_le_bytes());
+                    bytes.extend_from_slice(bound.as_bytes());
+                }
+
+                bytes
+            }
+            LangType::Dyn(trait_name) => {
+                let mut bytes = vec![7];
+                bytes.extend_from_slice(&(trait_name.len() as u16).to_le_bytes());
+                bytes.extend_from_slice(trait_name.as_bytes());
+
+                bytes
+            }
+            LangType::Tuple(elems) => {
+                let mut bytes = vec![8, elems.len() as u8];
+                for elem in elems {
+                    bytes.extend_from_slice(elem.to_bytes().as_slice());
+                }
+
+                bytes
+            }
+            LangType::Slice(ty) => {
+                let mut bytes = vec![9];
+                bytes.extend_from_slice(ty.to_bytes().as_slice());
+
+                bytes
+            }
+            LangType::Vec(ty) => {
+                let mut bytes = vec![10];
+                bytes.extend_from_slice(ty.to_bytes().as_slice());
+
+                bytes
+            }
+            LangType::Option(ty) => {
+                let mut bytes = vec![11];
+                bytes.extend_from_slice(ty.to_bytes().as_slice());
+
+                bytes
+            }
+            LangType::Result(ty, err) => {
+                let mut bytes = vec![12];
+                bytes.extend_from_slice(ty.to_bytes().as_slice());
+                bytes.extend_from_slice(err.to_bytes().as_slice());
+
+                bytes
+            }
+            LangType::Newtype { name, ty } => {
+                let mut bytes = vec![13];
+                bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+                bytes.extend_from_slice(name.as_bytes());
+                bytes.extend_from_slice(ty.to_bytes().as_slice());
+
                 bytes
             }
         }
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        LangType::decode(bytes).map(|(ty, _)| ty)
+    }
+
+    fn decode(bytes: &[u8]) -> Option<(Self, usize)> {
         match bytes.get(0)? {
-            0 => Some(LangType::UNRESOLVED),
-            2 => match bytes.get(1)? {
-                0 => Some(LangType::Primitives(Primitive::Int)),
-                1 => Some(LangType::Primitives(Primitive::Float)),
-                2 => Some(LangType::Primitives(Primitive::Char)),
-                3 => Some(LangType::Primitives(Primitive::Bool)),
-                4 => Some(LangType::Primitives(Primitive::Unit)),
-                _ => None,
-            },
+            0 => Some((LangType::UNRESOLVED, 1)),
+            2 => Some((LangType::Primitives(Primitive::from_byte(*bytes.get(1)?)?), 2)),
+            3 => {
+                let (ty, len) = LangType::decode(&bytes[1..])?;
+                let size = u64::from_le_bytes(bytes.get(1 + len..9 + len)?.try_into().ok()?);
+
+                Some((LangType::StaticArray { size, ty: Box::new(ty) }, 9 + len))
+            }
+            4 => {
+                let count = *bytes.get(1)? as usize;
+                let mut offset = 2;
+                let mut params = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    let (param, len) = LangType::decode(bytes.get(offset..)?)?;
+                    params.push(param);
+                    offset += len;
+                }
+
+                let (ret, len) = LangType::decode(bytes.get(offset..)?)?;
+
+                Some((LangType::Function { params, ret: Box::new(ret) }, offset + len))
+            }
+            5 => {
+                let (name, len) = decode_name(bytes.get(1..)?)?;
+
+                Some((LangType::Struct(name), 1 + len))
+            }
+            6 => {
+                let (name, mut offset) = decode_name(bytes.get(1..)?)?;
+                offset += 1;
+
+                let count = *bytes.get(offset)? as usize;
+                offset += 1;
+
+                let mut bounds = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    let (bound, len) = decode_name(bytes.get(offset..)?)?;
+                    bounds.push(bound);
+                    offset += len;
+                }
+
+                Some((LangType::Param { name, bounds }, offset))
+            }
+            7 => {
+                let (trait_name, len) = decode_name(bytes.get(1..)?)?;
+
+                Some((LangType::Dyn(trait_name), 1 + len))
+            }
+            8 => {
+                let count = *bytes.get(1)? as usize;
+                let mut offset = 2;
+                let mut elems = Vec::with_capacity(count);
+
+                for _ in 0..count {
+                    let (elem, len) = LangType::decode(bytes.get(offset..)?)?;
+                    elems.push(elem);
+                    offset += len;
+                }
+
+                Some((LangType::Tuple(elems), offset))
+            }
+            9 => {
+                let (ty, len) = LangType::decode(bytes.get(1..)?)?;
+
+                Some((LangType::Slice(Box::new(ty)), 1 + len))
+            }
+            10 => {
+                let (ty, len) = LangType::decode(bytes.get(1..)?)?;
+
+                Some((LangType::Vec(Box::new(ty)), 1 + len))
+            }
+            11 => {
+                let (ty, len) = LangType::decode(bytes.get(1..)?)?;
+
+                Some((LangType::Option(Box::new(ty)), 1 + len))
+            }
+            12 => {
+                let (ty, len) = LangType::decode(bytes.get(1..)?)?;
+                let (err, err_len) = LangType::decode(bytes.get(1 + len..)?)?;
+
+                Some((LangType::Result(Box::new(ty), Box::new(err)), 1 + len + err_len))
+            }
+            13 => {
+                let (name, name_len) = decode_name(bytes.get(1..)?)?;
+                let (ty, len) = LangType::decode(bytes.get(1 + name_len..)?)?;
+
+                Some((LangType::Newtype { name, ty: Box::new(ty) }, 1 + name_len + len))
+            }
             _ => None,
         }
     }
 }
 
+fn decode_name(bytes: &[u8]) -> Option<(String, usize)> {
+    let len = u16::from_le_bytes(bytes.get(0..2)?.try_into().ok()?) as usize;
+    let name = std::str::from_utf8(bytes.get(2..2 + len)?).ok()?;
+
+    Some((name.to_string(), 2 + len))
+}
+
+// types as they are written in source, for diagnostics
+impl fmt::Display for LangType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |types: &[LangType]| types.iter().map(|ty| ty.to_string()).collect::<Vec<_>>().join(", ");
+
+        match self {
+            LangType::Primitives(p) => write!(f, "{}", p.name()),
+            LangType::StaticArray { ty, size } => write!(f, "[{}; {}]", ty, size),
+            LangType::Function { params, ret } => write!(f, "fn({}) -> {}", list(params), ret),
+            LangType::Struct(name) | LangType::Param { name, .. } | LangType::Newtype { name, .. } => write!(f, "{}", name),
+            LangType::Dyn(trait_name) => write!(f, "dyn {}", trait_name),
+            LangType::Tuple(elems) => write!(f, "({})", list(elems)),
+            LangType::Slice(ty) => write!(f, "[{}]", ty),
+            LangType::Vec(ty) => write!(f, "Vec<{}>", ty),
+            LangType::Option(ty) => write!(f, "Option<{}>", ty),
+            LangType::Result(ty, err) => write!(f, "Result<{}, {}>", ty, err),
+            LangType::UNRESOLVED => write!(f, "_"),
+            LangType::Error => write!(f, "{{error}}")
+        }
+    }
+}
+
 impl Primitive {
+    // every primitive that can be written as a type name
+    pub const NAMES: [&'static str; 16] = [
+        "Int", "Float", "Char", "Bool", "I8", "I16", "I32", "I64", "I128", "U8", "U16", "U32", "U64", "U128", "F32", "F64"
+    ];
+
+    pub fn from_byte(byte: u8) -> Option<Primitive> {
+        match byte {
+            0 => Some(Primitive::Int),
+            1 => Some(Primitive::Float),
+            2 => Some(Primitive::Char),
+            3 => Some(Primitive::Bool),
+            4 => Some(Primitive::Unit),
+            5 => Some(Primitive::I8),
+            6 => Some(Primitive::I16),
+            7 => Some(Primitive::I32),
+            8 => Some(Primitive::I64),
+            9 => Some(Primitive::I128),
+            10 => Some(Primitive::U8),
+            11 => Some(Primitive::U16),
+            12 => Some(Primitive::U32),
+            13 => Some(Primitive::U64),
+            14 => Some(Primitive::U128),
+            15 => Some(Primitive::F32),
+            16 => Some(Primitive::F64),
+            _ => None,
+        }
+    }
+
     pub fn to_byte(&self) -> u8 {
         match self {
             Primitive::Int => 0,
@@ -89,6 +457,159 @@ impl Primitive {
             Primitive::Char => 2,
             Primitive::Bool => 3,
             Primitive::Unit => 4,
+            Primitive::I8 => 5,
+            Primitive::I16 => 6,
+            Primitive::I32 => 7,
+            Primitive::I64 => 8,
+            Primitive::I128 => 9,
+            Primitive::U8 => 10,
+            Primitive::U16 => 11,
+            Primitive::U32 => 12,
+            Primitive::U64 => 13,
+            Primitive::U128 => 14,
+            Primitive::F32 => 15,
+            Primitive::F64 => 16,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Primitive> {
+        match name {
+            "Int" => Some(Primitive::Int),
+            "Float" => Some(Primitive::Float),
+            "Char" => Some(Primitive::Char),
+            "Bool" => Some(Primitive::Bool),
+            "I8" => Some(Primitive::I8),
+            "I16" => Some(Primitive::I16),
+            "I32" => Some(Primitive::I32),
+            "I64" => Some(Primitive::I64),
+            "I128" => Some(Primitive::I128),
+            "U8" => Some(Primitive::U8),
+            "U16" => Some(Primitive::U16),
+            "U32" => Some(Primitive::U32),
+            "U64" => Some(Primitive::U64),
+            "U128" => Some(Primitive::U128),
+            "F32" => Some(Primitive::F32),
+            "F64" => Some(Primitive::F64),
+            _ => None
+        }
+    }
+
+    pub fn from_suffix(suffix: &str) -> Option<Primitive> {
+        match suffix {
+            "i8" => Some(Primitive::I8),
+            "i16" => Some(Primitive::I16),
+            "i32" => Some(Primitive::I32),
+            "i64" => Some(Primitive::I64),
+            "i128" => Some(Primitive::I128),
+            "u8" => Some(Primitive::U8),
+            "u16" => Some(Primitive::U16),
+            "u32" => Some(Primitive::U32),
+            "u64" => Some(Primitive::U64),
+            "u128" => Some(Primitive::U128),
+            "f32" => Some(Primitive::F32),
+            "f64" => Some(Primitive::F64),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Primitive::Int => "Int",
+            Primitive::Float => "Float",
+            Primitive::Char => "Char",
+            Primitive::Bool => "Bool",
+            Primitive::Unit => "()",
+            Primitive::I8 => "I8",
+            Primitive::I16 => "I16",
+            Primitive::I32 => "I32",
+            Primitive::I64 => "I64",
+            Primitive::I128 => "I128",
+            Primitive::U8 => "U8",
+            Primitive::U16 => "U16",
+            Primitive::U32 => "U32",
+            Primitive::U64 => "U64",
+            Primitive::U128 => "U128",
+            Primitive::F32 => "F32",
+            Primitive::F64 => "F64",
+        }
+    }
+
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Primitive::Int => "i",
+            Primitive::Float => "f",
+            Primitive::Char => "c",
+            Primitive::Bool => "b",
+            Primitive::Unit => "u",
+            Primitive::I8 => "i8",
+            Primitive::I16 => "i16",
+            Primitive::I32 => "i32",
+            Primitive::I64 => "i64",
+            Primitive::I128 => "i128",
+            Primitive::U8 => "u8",
+            Primitive::U16 => "u16",
+            Primitive::U32 => "u32",
+            Primitive::U64 => "u64",
+            Primitive::U128 => "u128",
+            Primitive::F32 => "f32",
+            Primitive::F64 => "f64",
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self,
+            Primitive::Int | Primitive::Char
+            | Primitive::I8 | Primitive::I16 | Primitive::I32 | Primitive::I64 | Primitive::I128
+            | Primitive::U8 | Primitive::U16 | Primitive::U32 | Primitive::U64 | Primitive::U128
+        )
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Primitive::Float | Primitive::F32 | Primitive::F64)
+    }
+
+    pub fn is_unsigned(&self) -> bool {
+        matches!(self, Primitive::U8 | Primitive::U16 | Primitive::U32 | Primitive::U64 | Primitive::U128)
+    }
+
+    pub fn bit_width(&self) -> u32 {
+        match self {
+            Primitive::Bool => 1,
+            Primitive::Char | Primitive::I8 | Primitive::U8 => 8,
+            Primitive::I16 | Primitive::U16 => 16,
+            Primitive::I32 | Primitive::U32 | Primitive::F32 => 32,
+            Primitive::Int | Primitive::I64 | Primitive::U64 | Primitive::Float | Primitive::F64 => 64,
+            Primitive::I128 | Primitive::U128 => 128,
+            Primitive::Unit => 0,
+        }
+    }
+
+    // whether an integer can be stored in this type without changing its value
+    pub fn fits_integer(&self, val: i128) -> bool {
+        match self {
+            Primitive::I128 => true,
+            Primitive::U128 => val >= 0,
+            _ if self.is_unsigned() => val >= 0 && val < 1 << self.bit_width(),
+            _ => {
+                let limit = 1i128 << (self.bit_width() - 1);
+                val >= -limit && val < limit
+            }
+        }
+    }
+
+    // a literal is its magnitude and a sign, only U128 takes magnitudes beyond the range of I128
+    pub fn fits_literal(&self, magnitude: u128, negative: bool) -> bool {
+        match i128::try_from(magnitude) {
+            Ok(val) => self.fits_integer(if negative { -val } else { val }),
+            Err(_) if negative => magnitude == i128::MIN.unsigned_abs() && self.fits_integer(i128::MIN),
+            Err(_) => *self == Primitive::U128
+        }
+    }
+
+    pub fn fits_float(&self, val: f64) -> bool {
+        match self {
+            Primitive::F32 => !val.is_finite() || val.abs() <= f32::MAX as f64,
+            _ => true
         }
     }
 }
\ No newline at end of file
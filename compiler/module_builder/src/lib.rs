@@ -1,53 +1,181 @@
-use std::{fs::File, io::Read, path::Path};
+use std::{fs::File, io::Read, path::{Path, PathBuf}};
 
+use ast::ParsedFile;
 use ast_lowering::translate_to_hir;
+use const_eval::evaluate_consts;
 use hir_resolver::resolve_module;
 use llvm_codegen::generate_object_code;
-use middle::GlobalCtx;
+use middle::{BuildType, GlobalCtx, ModuleInterface};
 use parser::parse_file;
 use type_checker::validate_hir;
 use types_lowering::type_hir_module;
 
+// the object code of one module, an executable links the objects of all modules it imports
+pub struct ModuleObject {
+    // full module path, like `net.http`
+    pub name: String,
+    pub code: Vec<u8>
+}
+
 pub struct BuildingModule<'a> {
+    // full module path, like `net.http`
     pub name: &'a str,
     pub path: &'a str,
-    pub files: Vec<&'a str>
+    // project root that imported module paths are looked up from
+    pub root: &'a str,
+    pub files: Vec<&'a str>,
+    // infer the return types of private functions that leave them out, imported modules are built alike
+    pub infer_return_types: bool
 }
 
-pub fn build_module(module: &BuildingModule) {
-    let mut ctx = GlobalCtx::new(
-        module.name.to_string(), 
-        "x86_64".to_string(), 
-        middle::BuildType::Executable
-    );
+// `import net.http` refers to the module in the `net/http` directory under the project root
+pub fn module_dir(root: &Path, module_path: &[&str]) -> PathBuf {
+    module_path.iter().fold(root.to_path_buf(), |dir, segment| dir.join(segment))
+}
+
+pub fn module_sources(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = dir.read_dir() else {
+        return Vec::new();
+    };
+
+    let mut sources: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "cd"))
+        .collect();
 
-    let mut parsed = Vec::new();
+    sources.sort();
+    sources
+}
 
-    for file in &module.files {
-        let file_name = Path::new(file)
+fn parse_sources(files: &[PathBuf]) -> Vec<ParsedFile<'static>> {
+    files.iter().map(|file| {
+        let file_name = file
             .file_stem()
             .and_then(|os_str| os_str.to_str())
-            .unwrap_or("unknown");
+            .unwrap_or("unknown")
+            .to_string();
 
-        let mut file = File::open(file).unwrap();
         let mut content = String::new();
-        file.read_to_string(&mut content).unwrap();
-        let boxed_content = Box::leak(content.into_boxed_str());
+        File::open(file).unwrap().read_to_string(&mut content).unwrap();
+
+        parse_file(Box::leak(file_name.into_boxed_str()), Box::leak(content.into_boxed_str()))
+    }).collect()
+}
 
-        parsed.push(parse_file(file_name, boxed_content));
+// modules compiled so far, a module imported from several others is only compiled once
+#[derive(Default)]
+struct BuiltModules {
+    interfaces: Vec<ModuleInterface>,
+    objects: Vec<ModuleObject>
+}
+
+// runs the front end over a module, the modules it imports are built first so that
+// calls into them can be checked against their interfaces
+fn analyze_module<'a>(ctx: &mut GlobalCtx<'a>, parsed: &'a [ParsedFile<'a>], root: &Path, loading: &[String], built: &mut BuiltModules) {
+    for ast in parsed {
+        let file = translate_to_hir(ctx, ast);
+        ctx.module_files.push(file);
     }
 
-    for ast in &parsed {
-        ctx.module_files.push(
-            translate_to_hir(ast)
-        );
+    let mut interfaces: Vec<ModuleInterface> = Vec::new();
+
+    for file in &ctx.module_files {
+        for import in file.imports.iter().filter(|import| !import.system) {
+            let name = import.path.join(".");
+
+            if loading.contains(&name) {
+                ctx.error(format!("import cycle: {} -> {}", loading.join(" -> "), name));
+                continue;
+            }
+
+            if interfaces.iter().any(|interface| interface.name == name) {
+                continue;
+            }
+
+            if let Some(interface) = built.interfaces.iter().find(|interface| interface.name == name) {
+                interfaces.push(interface.clone());
+                continue;
+            }
+
+            let dir = module_dir(root, &import.path);
+            let sources = module_sources(&dir);
+
+            if sources.is_empty() {
+                ctx.error(format!("module {} imported in {} not found, expected sources in {}", 
+                    name, file.name, dir.display()));
+                continue;
+            }
+
+            interfaces.push(build_import(name, root, &sources, loading, ctx.infer_return_types, built));
+        }
     }
+
+    // without the interfaces every use of the missing modules would be reported again
+    ctx.abort_on_errors();
+
+    ctx.module_interfaces = interfaces;
     
-    resolve_module(&mut ctx);
+    resolve_module(ctx);
+
+    type_hir_module(ctx);
+
+    validate_hir(ctx);
+
+    evaluate_consts(ctx);
+}
+
+fn build_import(
+    name: String, 
+    root: &Path, 
+    sources: &[PathBuf], 
+    loading: &[String], 
+    infer_return_types: bool, 
+    built: &mut BuiltModules
+) -> ModuleInterface {
+    let parsed = parse_sources(sources);
+
+    let mut loading = loading.to_vec();
+    loading.push(name.clone());
+
+    let mut ctx = GlobalCtx::new(name.clone(), "x86_64".to_string(), BuildType::ModulePack);
+    ctx.infer_return_types = infer_return_types;
+    analyze_module(&mut ctx, &parsed, root, &loading, built);
+
+    print_warnings(&ctx);
+
+    let interface = ctx.interface();
+
+    built.interfaces.push(interface.clone());
+    built.objects.push(ModuleObject { name, code: generate_object_code(&ctx) });
+
+    interface
+}
+
+fn print_warnings(ctx: &GlobalCtx) {
+    for warning in ctx.module_warnings.borrow().iter() {
+        eprintln!("warning: {}", warning);
+    }
+}
+
+// the object of the module itself comes first, followed by the objects of everything it imports
+pub fn build_module(module: &BuildingModule) -> Vec<ModuleObject> {
+    let mut ctx = GlobalCtx::new(
+        module.name.to_string(), 
+        "x86_64".to_string(), 
+        BuildType::Executable
+    );
+    ctx.infer_return_types = module.infer_return_types;
+
+    let files: Vec<PathBuf> = module.files.iter().map(PathBuf::from).collect();
+    let parsed = parse_sources(&files);
+
+    let mut built = BuiltModules::default();
+    analyze_module(&mut ctx, &parsed, Path::new(module.root), &[module.name.to_string()], &mut built);
 
-    type_hir_module(&mut ctx);
+    print_warnings(&ctx);
 
-    validate_hir(&ctx);
+    let mut objects = vec![ModuleObject { name: module.name.to_string(), code: generate_object_code(&ctx) }];
+    objects.append(&mut built.objects);
 
-    generate_object_code(&ctx);
+    objects
 }
@@ -1,15 +1,22 @@
 use std::io::{Read, Result, Write};
 use std::collections::HashMap;
 use byteorder::{LittleEndian, WriteBytesExt, ReadBytesExt};
+use hir::{HirModuleItem, HirVisibility};
 use middle::ty::LangType;
 use middle::GlobalCtx;
 
+const SYMBOL_FLAG_CONST: u8 = 1;
+const ALIAS_FLAG_DISTINCT: u8 = 1;
+
 #[derive(Debug)]
 struct FileHeader {
     magic: [u8; 4],
     version: u16,
     symbol_count: u32,
     type_count: u32,
+    trait_count: u32,
+    impl_count: u32,
+    alias_count: u32,
     string_pool_size: u32,
 }
 
@@ -19,6 +26,9 @@ impl FileHeader {
         writer.write_u16::<LittleEndian>(self.version)?;
         writer.write_u32::<LittleEndian>(self.symbol_count)?;
         writer.write_u32::<LittleEndian>(self.type_count)?;
+        writer.write_u32::<LittleEndian>(self.trait_count)?;
+        writer.write_u32::<LittleEndian>(self.impl_count)?;
+        writer.write_u32::<LittleEndian>(self.alias_count)?;
         writer.write_u32::<LittleEndian>(self.string_pool_size)?;
         Ok(())
     }
@@ -40,6 +50,54 @@ impl SymbolRecord {
     }
 }
 
+#[derive(Debug)]
+struct TraitRecord {
+    name_offset: u32,
+    methods: Vec<(u32, u32)>,
+}
+
+impl TraitRecord {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<LittleEndian>(self.name_offset)?;
+        writer.write_u16::<LittleEndian>(self.methods.len() as u16)?;
+        for (name_offset, type_index) in &self.methods {
+            writer.write_u32::<LittleEndian>(*name_offset)?;
+            writer.write_u32::<LittleEndian>(*type_index)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct ImplRecord {
+    type_offset: u32,
+    trait_offset: u32,
+}
+
+impl ImplRecord {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<LittleEndian>(self.type_offset)?;
+        writer.write_u32::<LittleEndian>(self.trait_offset)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct AliasRecord {
+    name_offset: u32,
+    type_index: u32,
+    flags: u8,
+}
+
+impl AliasRecord {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<LittleEndian>(self.name_offset)?;
+        writer.write_u32::<LittleEndian>(self.type_index)?;
+        writer.write_u8(self.flags)?;
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 struct TypeRecord {
     data_size: u16,
@@ -63,36 +121,95 @@ fn marshall_symbol_tables<'a, W: Write>(
     let mut type_indices = HashMap::new();
     let mut symbol_records = Vec::new();
 
-    for (name, hir_id) in &ctx.module_exports {
-        let ty = ctx.module_ty_info.borrow()
-            .get_type(hir_id).unwrap().ty.clone();
-
+    let mut intern_type = |ty: &LangType| -> u32 {
         let type_bytes = ty.to_bytes();
-        let type_idx = *type_indices.entry(type_bytes.clone())
+        *type_indices.entry(type_bytes.clone())
             .or_insert_with(|| {
                 type_pool.push(TypeRecord {
                     data_size: type_bytes.len() as u16,
                     data: type_bytes,
                 });
                 (type_pool.len() - 1) as u32
-            });
+            })
+    };
 
+    let intern_name = |string_pool: &mut Vec<u8>, name: &str| -> u32 {
         let name_offset = string_pool.len() as u32;
         string_pool.extend_from_slice(name.as_bytes());
         string_pool.push(0);
+        name_offset
+    };
+
+    for (name, hir_id) in &ctx.module_exports {
+        let ty = ctx.module_ty_info.borrow()
+            .get_type(hir_id).unwrap().ty.clone();
+
+        let type_idx = intern_type(&ty);
+
+        let name_offset = intern_name(&mut string_pool, name);
+
+        let flags = if ctx.module_consts.borrow().get_value(hir_id).is_some() {
+            SYMBOL_FLAG_CONST
+        } else {
+            0
+        };
 
         symbol_records.push(SymbolRecord {
             name_offset,
             type_index: type_idx,
-            flags: 0,
+            flags,
+        });
+    }
+
+    // pub traits and the impls of pub types are part of the interface, so importing modules can implement them
+    let interface = ctx.interface();
+    let mut trait_records = Vec::new();
+    let mut impl_records = Vec::new();
+
+    for module_trait in &interface.traits {
+        let name_offset = intern_name(&mut string_pool, &module_trait.name);
+
+        let methods = module_trait.methods.iter().map(|(name, ty)| {
+            (intern_name(&mut string_pool, name), intern_type(ty))
+        }).collect();
+
+        trait_records.push(TraitRecord { name_offset, methods });
+    }
+
+    for (target, trait_name) in &interface.impls {
+        impl_records.push(ImplRecord {
+            type_offset: intern_name(&mut string_pool, target),
+            trait_offset: intern_name(&mut string_pool, trait_name),
         });
     }
 
+    // pub type aliases and newtypes, with the type they stand for
+    let mut alias_records = Vec::new();
+
+    for file in &ctx.module_files {
+        for item in &file.items {
+            let HirModuleItem::TypeAlias { name, visibility: HirVisibility::Public, .. } = item else {
+                continue;
+            };
+
+            let info = ctx.module_type_aliases.borrow().get(name).cloned().unwrap();
+
+            alias_records.push(AliasRecord {
+                name_offset: intern_name(&mut string_pool, name),
+                type_index: intern_type(&info.ty),
+                flags: if info.distinct { ALIAS_FLAG_DISTINCT } else { 0 },
+            });
+        }
+    }
+
     let header = FileHeader {
         magic: *b"COMO",
-        version: 1,
+        version: 3,
         symbol_count: symbol_records.len() as u32,
         type_count: type_pool.len() as u32,
+        trait_count: trait_records.len() as u32,
+        impl_count: impl_records.len() as u32,
+        alias_count: alias_records.len() as u32,
         string_pool_size: string_pool.len() as u32,
     };
     header.write(writer)?;
@@ -107,6 +224,18 @@ fn marshall_symbol_tables<'a, W: Write>(
         type_record.write(writer)?;
     }
 
+    for record in trait_records {
+        record.write(writer)?;
+    }
+
+    for record in impl_records {
+        record.write(writer)?;
+    }
+
+    for record in alias_records {
+        record.write(writer)?;
+    }
+
     Ok(())
 }
 
@@ -118,7 +247,19 @@ pub fn marshall_module<'a, W: Write>(
     Ok(())
 }
 
-pub fn unmarshall_and_print<R: Read>(reader: &mut R) -> Result<()> {
+// the contents of a .como file, with names and types decoded
+#[derive(Debug, PartialEq)]
+struct ComoModule {
+    version: u16,
+    string_pool_size: u32,
+    type_count: u32,
+    symbols: Vec<(String, LangType, bool)>,
+    traits: Vec<(String, Vec<(String, LangType)>)>,
+    impls: Vec<(String, String)>,
+    aliases: Vec<(String, LangType, bool)>,
+}
+
+fn unmarshall_module<R: Read>(reader: &mut R) -> Result<ComoModule> {
     let mut magic = [0u8; 4];
     reader.read_exact(&mut magic)?;
     
@@ -132,14 +273,11 @@ pub fn unmarshall_and_print<R: Read>(reader: &mut R) -> Result<()> {
     let version = reader.read_u16::<LittleEndian>()?;
     let symbol_count = reader.read_u32::<LittleEndian>()?;
     let type_count = reader.read_u32::<LittleEndian>()?;
+    let trait_count = reader.read_u32::<LittleEndian>()?;
+    let impl_count = reader.read_u32::<LittleEndian>()?;
+    let alias_count = reader.read_u32::<LittleEndian>()?;
     let string_pool_size = reader.read_u32::<LittleEndian>()?;
 
-    println!("=== File Header ===");
-    println!("Version: {}", version);
-    println!("Symbols: {}", symbol_count);
-    println!("Types: {}", type_count);
-    println!("String pool size: {} bytes", string_pool_size);
-
     let mut string_pool = vec![0u8; string_pool_size as usize];
     reader.read_exact(&mut string_pool)?;
     
@@ -147,9 +285,9 @@ pub fn unmarshall_and_print<R: Read>(reader: &mut R) -> Result<()> {
     for _ in 0..symbol_count {
         let name_offset = reader.read_u32::<LittleEndian>()?;
         let type_index = reader.read_u32::<LittleEndian>()?;
-        let _flags = reader.read_u8()?;
+        let flags = reader.read_u8()?;
         
-        symbols.push((name_offset, type_index));
+        symbols.push((name_offset, type_index, flags));
     }
 
     let mut types = Vec::with_capacity(type_count as usize);
@@ -161,25 +299,99 @@ pub fn unmarshall_and_print<R: Read>(reader: &mut R) -> Result<()> {
         types.push(data);
     }
 
-    println!("\n=== Symbols ===");
-    for (name_offset, type_idx) in symbols {
-        let mut name_bytes = Vec::new();
-        let mut pos = name_offset as usize;
-        while pos < string_pool.len() && string_pool[pos] != 0 {
-            name_bytes.push(string_pool[pos]);
-            pos += 1;
+    let read_type = |type_idx: u32| types.get(type_idx as usize)
+        .and_then(|type_data| LangType::from_bytes(type_data))
+        .unwrap_or(LangType::UNRESOLVED);
+
+    let mut traits = Vec::with_capacity(trait_count as usize);
+    for _ in 0..trait_count {
+        let name_offset = reader.read_u32::<LittleEndian>()?;
+        let method_count = reader.read_u16::<LittleEndian>()?;
+
+        let mut methods = Vec::with_capacity(method_count as usize);
+        for _ in 0..method_count {
+            let method_name = reader.read_u32::<LittleEndian>()?;
+            let type_index = reader.read_u32::<LittleEndian>()?;
+            methods.push((read_string(&string_pool, method_name), read_type(type_index)));
         }
-        let name = String::from_utf8_lossy(&name_bytes);
 
-        if let Some(type_data) = types.get(type_idx as usize) {
-            let ty = LangType::from_bytes(type_data)
-                .unwrap_or(LangType::UNRESOLVED);
-            
-            println!("{:<20} : {:?}", name, ty);
-        } else {
-            println!("{:<20} : [UNKNOWN TYPE]", name);
+        traits.push((read_string(&string_pool, name_offset), methods));
+    }
+
+    let mut impls = Vec::with_capacity(impl_count as usize);
+    for _ in 0..impl_count {
+        let type_offset = reader.read_u32::<LittleEndian>()?;
+        let trait_offset = reader.read_u32::<LittleEndian>()?;
+        impls.push((read_string(&string_pool, type_offset), read_string(&string_pool, trait_offset)));
+    }
+
+    let mut aliases = Vec::with_capacity(alias_count as usize);
+    for _ in 0..alias_count {
+        let name_offset = reader.read_u32::<LittleEndian>()?;
+        let type_index = reader.read_u32::<LittleEndian>()?;
+        let flags = reader.read_u8()?;
+        aliases.push((read_string(&string_pool, name_offset), read_type(type_index), flags & ALIAS_FLAG_DISTINCT != 0));
+    }
+
+    let symbols = symbols.into_iter()
+        .map(|(name_offset, type_idx, flags)| {
+            (read_string(&string_pool, name_offset), read_type(type_idx), flags & SYMBOL_FLAG_CONST != 0)
+        })
+        .collect();
+
+    Ok(ComoModule { version, string_pool_size, type_count, symbols, traits, impls, aliases })
+}
+
+pub fn unmarshall_and_print<R: Read>(reader: &mut R) -> Result<()> {
+    let module = unmarshall_module(reader)?;
+
+    println!("=== File Header ===");
+    println!("Version: {}", module.version);
+    println!("Symbols: {}", module.symbols.len());
+    println!("Types: {}", module.type_count);
+    println!("Traits: {}", module.traits.len());
+    println!("Impls: {}", module.impls.len());
+    println!("Type aliases: {}", module.aliases.len());
+    println!("String pool size: {} bytes", module.string_pool_size);
+
+    println!("\n=== Symbols ===");
+    for (name, ty, constant) in module.symbols {
+        let kind = if constant { "const" } else { "fn" };
+        println!("{:<5} {:<20} : {:?}", kind, name, ty);
+    }
+
+    println!("\n=== Traits ===");
+    for (name, methods) in module.traits {
+        println!("trait {}", name);
+
+        for (method_name, ty) in methods {
+            println!("    fn {:<17} : {:?}", method_name, ty);
         }
     }
 
+    println!("\n=== Impls ===");
+    for (target, trait_name) in module.impls {
+        println!("impl {} for {}", trait_name, target);
+    }
+
+    println!("\n=== Type aliases ===");
+    for (name, ty, distinct) in module.aliases {
+        let kind = if distinct { "newtype" } else { "type" };
+        println!("{:<7} {:<18} = {:?}", kind, name, ty);
+    }
+
     Ok(())
-}
\ No newline at end of file
+}
+
+fn read_string(string_pool: &[u8], offset: u32) -> String {
+    let mut name_bytes = Vec::new();
+    let mut pos = offset as usize;
+    while pos < string_pool.len() && string_pool[pos] != 0 {
+        name_bytes.push(string_pool[pos]);
+        pos += 1;
+    }
+    String::from_utf8_lossy(&name_bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests;
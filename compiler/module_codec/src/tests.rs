@@ -0,0 +1,98 @@
+use ast_lowering::translate_to_hir;
+use const_eval::evaluate_consts;
+use hir_resolver::resolve_module;
+use middle::{BuildType, GlobalCtx};
+use parser::parse_file;
+use types_lowering::type_hir_module;
+
+use crate::{marshall_module, unmarshall_module, ComoModule};
+
+const SHAPES: &str = "
+    pub type Meters = Float;
+    pub newtype Id = Int;
+    type Hidden = Bool;
+    pub const SIDES: Int = 4;
+    pub trait Area {
+        fn area(self) -> Int;
+        fn scaled(self, by: Int) -> Self;
+    }
+    trait Secret {
+        fn secret(self) -> Int;
+    }
+    pub struct Square { side: Int }
+    struct Blob { size: Int }
+    impl Area for Square {
+        fn area(self) -> Int { ret self.side * self.side; }
+        fn scaled(self, by: Int) -> Square { ret Square { side: self.side * by }; }
+    }
+    impl Area for Blob {
+        fn area(self) -> Int { ret self.size; }
+        fn scaled(self, by: Int) -> Blob { ret Blob { size: self.size * by }; }
+    }
+    pub fn unit() -> Square { ret Square { side: 1 }; }
+    pub fn length(side: Meters) -> Meters { ret side * 4.0; }
+    fn private() -> Int { ret 0; }
+";
+
+// writes the interface of the source to a .como file and reads it back
+fn round_trip(source: &str) -> ComoModule {
+    let parsed = parse_file("shapes", source);
+
+    let mut ctx = GlobalCtx::new("shapes".to_string(), "x86_64".to_string(), BuildType::ModulePack);
+    let file = translate_to_hir(&ctx, &parsed);
+    ctx.module_files.push(file);
+
+    resolve_module(&mut ctx);
+    type_hir_module(&mut ctx);
+    evaluate_consts(&ctx);
+    assert_eq!(ctx.module_errors.take(), Vec::<String>::new());
+
+    let mut bytes = Vec::new();
+    marshall_module(&ctx, &mut bytes).unwrap();
+
+    unmarshall_module(&mut bytes.as_slice()).unwrap()
+}
+
+#[test]
+fn round_trips_public_symbols_with_their_types() {
+    let module = round_trip(SHAPES);
+
+    let mut symbols: Vec<String> = module.symbols.iter()
+        .map(|(name, ty, constant)| format!("{} {}: {}", if *constant { "const" } else { "fn" }, name, ty))
+        .collect();
+    symbols.sort();
+
+    assert_eq!(symbols, [
+        "const SIDES: Int",
+        "fn length: fn(Float) -> Float",
+        "fn unit: fn() -> Square"
+    ]);
+}
+
+#[test]
+fn round_trips_public_traits_and_impls() {
+    let module = round_trip(SHAPES);
+
+    let traits: Vec<(String, Vec<String>)> = module.traits.iter()
+        .map(|(name, methods)| (name.clone(), methods.iter().map(|(method, ty)| format!("{}: {}", method, ty)).collect()))
+        .collect();
+
+    assert_eq!(traits, [("Area".to_string(), vec!["area: fn(Self) -> Int".to_string(), "scaled: fn(Self, Int) -> Self".to_string()])]);
+    assert_eq!(module.impls, [("Square".to_string(), "Area".to_string())]);
+}
+
+#[test]
+fn round_trips_public_type_aliases() {
+    let module = round_trip(SHAPES);
+
+    let aliases: Vec<String> = module.aliases.iter()
+        .map(|(name, ty, distinct)| format!("{} {} = {}", if *distinct { "newtype" } else { "type" }, name, ty))
+        .collect();
+
+    assert_eq!(aliases, ["type Meters = Float", "newtype Id = Int"]);
+}
+
+#[test]
+fn rejects_files_of_other_formats() {
+    assert!(unmarshall_module(&mut b"ELF\0".as_slice()).is_err());
+}
@@ -11,4 +11,6 @@ pub fn parse_file<'a>(file_name: &'a str, content: &'a str) -> ParsedFile<'a> {
     result.name = file_name;
     
     result
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,69 @@
+use ast::{AstDefinitions, AstExpr, ExprTy};
+use tokens::Token;
+
+use crate::parse_file;
+
+fn type_text(ty: &ExprTy) -> String {
+    match ty {
+        ExprTy::Simple(name) => name.to_string(),
+        ExprTy::Applied { name, args } => {
+            let args: Vec<String> = args.iter().map(type_text).collect();
+            format!("{}<{}>", name, args.join(", "))
+        }
+        ExprTy::Function { params, ret } => {
+            let params: Vec<String> = params.iter().map(type_text).collect();
+            let ret = ret.as_ref().map(|ret| format!(" -> {}", type_text(ret))).unwrap_or_default();
+            format!("fn({}){}", params.join(", "), ret)
+        }
+        _ => format!("{:?}", ty)
+    }
+}
+
+fn param_types(source: &str) -> Vec<String> {
+    let parsed = parse_file("test", source);
+
+    let Some(AstDefinitions::Function(function)) = parsed.content.first() else {
+        panic!("expected a function");
+    };
+
+    function.args.iter().map(|(_, ty)| type_text(ty)).collect()
+}
+
+#[test]
+fn parses_generic_types() {
+    assert_eq!(param_types("fn f(v: Vec<Int>, r: Result<Int, Bool>) { 0; }"), ["Vec<Int>", "Result<Int, Bool>"]);
+}
+
+#[test]
+fn parses_nested_generic_types_closed_by_a_shift() {
+    assert_eq!(param_types("fn f(v: Option<Vec<Int>>) { 0; }"), ["Option<Vec<Int>>"]);
+    assert_eq!(param_types("fn f(v: Result<Int, Vec<Bool>>) { 0; }"), ["Result<Int, Vec<Bool>>"]);
+    assert_eq!(param_types("fn f(v: Result<Vec<Int>, Bool>) { 0; }"), ["Result<Vec<Int>, Bool>"]);
+    assert_eq!(param_types("fn f(v: Vec<Option<Vec<Int>>>) { 0; }"), ["Vec<Option<Vec<Int>>>"]);
+}
+
+#[test]
+fn parses_function_types_nested_in_generic_types() {
+    assert_eq!(param_types("fn f(v: Vec<fn(Int) -> Vec<Int>>) { 0; }"), ["Vec<fn(Int) -> Vec<Int>>"]);
+    assert_eq!(param_types("fn f(v: Vec<Vec<fn() -> Vec<Int>>>) { 0; }"), ["Vec<Vec<fn() -> Vec<Int>>>"]);
+}
+
+#[test]
+fn parses_shift_in_expressions() {
+    let parsed = parse_file("test", "fn f(a: Int) { a >> 2; }");
+
+    let Some(AstDefinitions::Function(function)) = parsed.content.first() else {
+        panic!("expected a function");
+    };
+
+    let AstExpr::Block(exprs) = function.body.as_ref() else {
+        panic!("expected a block");
+    };
+
+    assert!(matches!(&exprs[0], AstExpr::Binary(binary) if binary.operator == Token::SHR));
+}
+
+#[test]
+fn parses_nested_generic_types_with_spaced_brackets() {
+    assert_eq!(param_types("fn f(v: Option<Vec<Int> >) { 0; }"), ["Option<Vec<Int>>"]);
+}
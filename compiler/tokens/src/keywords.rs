@@ -15,5 +15,16 @@ lazy_static! {
         ("else", Token::ELSE),
         ("extern", Token::EXTERN),
         ("val", Token::VAL),
+        ("as", Token::AS),
+        ("struct", Token::STRUCT),
+        ("impl", Token::IMPL),
+        ("self", Token::SELF),
+        ("trait", Token::TRAIT),
+        ("for", Token::FOR),
+        ("dyn", Token::DYN),
+        ("in", Token::IN),
+        ("defer", Token::DEFER),
+        ("type", Token::TYPE),
+        ("newtype", Token::NEWTYPE),
     ]);
 }
\ No newline at end of file
@@ -3,12 +3,16 @@ pub mod keywords;
 #[derive(Clone, Debug, PartialEq, Copy)]
 pub enum Token<'input> {
     IDENTIFIER(&'input str),
-    INTEGER(i64),
+    // literals are lexed without their sign, a minus in front is applied by the parser
+    INTEGER(u128),
     FLOAT(f64),
+    TYPEDINTEGER(u128, &'input str),
+    TYPEDFLOAT(f64, &'input str),
     BOOL(bool),
     STR(&'input str),
     AND,
     OR,
+    PIPE,
     IF,
     ELSE,
     PLUS,
@@ -24,6 +28,9 @@ pub enum Token<'input> {
     FUNCTION,
     ASSIGN,
     GT, LT, EQ,
+    SHL, SHR,
+    // a `>` directly followed by another `>`, a shift in expressions and two closing brackets in types
+    JOINTGT,
     CONST,
     PUBLIC,
     PRIVATE,
@@ -33,6 +40,21 @@ pub enum Token<'input> {
     IMPORT,
     EXCLAMATION,
     VAL,
+    AS,
+    STRUCT,
+    IMPL,
+    SELF,
+    DOT,
+    TRAIT,
+    FOR,
+    DYN,
+    IN,
+    LSBRACKET,
+    RSBRACKET,
+    QUESTION,
+    DEFER,
+    TYPE,
+    NEWTYPE,
     URESOLVED,
 }
 
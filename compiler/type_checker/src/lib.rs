@@ -1,51 +1,609 @@
-use hir::{HirExpr, HirExprKind, HirModuleItem};
-use middle::GlobalCtx;
+use std::collections::BTreeMap;
 
-pub fn check_inner_expressions<'a>(ctx: &GlobalCtx<'a>, expr: &HirExpr<'a>) {
+use hir::{diverges, HirExpr, HirExprKind, HirId, HirModuleItem, HirPattern, HirVisibility};
+use middle::{ty::{LangType, Primitive}, GlobalCtx, TypeInfo};
+
+// a newtype converts to and from what it wraps only explicitly, through a cast
+fn is_valid_cast(from: &LangType, to: &LangType) -> bool {
+    let (LangType::Primitives(from), LangType::Primitives(to)) = (from.underlying(), to.underlying()) else {
+        return false;
+    };
+
+    if from == to {
+        return true;
+    }
+
+    match (from, to) {
+        // widening, narrowing and sign changes between integers, chars included
+        (f, t) if f.is_integer() && t.is_integer() => true,
+        (f, t) if f.is_integer() && t.is_float() => true,
+        (f, t) if f.is_float() && t.is_integer() => true,
+        (f, t) if f.is_float() && t.is_float() => true,
+        (Primitive::Bool, t) if t.is_integer() => true,
+        _ => false
+    }
+}
+
+// the type an expression has after implicit coercions were applied to it
+fn value_type(ctx: &GlobalCtx, expr: &HirExpr) -> LangType {
+    if let Some(target) = ctx.module_coercions.borrow().get_coercion(&expr.id) {
+        return target.clone();
+    }
+
+    ctx.module_ty_info.borrow().get_type(&expr.id).unwrap().ty.clone()
+}
+
+// a value whose error was reported gets the error type, so that the expressions using it are not reported again
+fn poison(ctx: &GlobalCtx, expr: &HirExpr) {
+    ctx.module_coercions.borrow_mut().remove_coercion(&expr.id);
+    ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: LangType::Error });
+}
+
+// values whose error was already reported match whatever they are compared with
+fn differ(expected: &LangType, actual: &LangType) -> bool {
+    expected != actual && !expected.has_error() && !actual.has_error()
+}
+
+fn mentions_self(ty: &LangType) -> bool {
+    match ty {
+        LangType::Param { name, .. } => name == "Self",
+        LangType::StaticArray { ty, .. } | LangType::Slice(ty) | LangType::Vec(ty) | LangType::Option(ty) => mentions_self(ty),
+        LangType::Result(ty, err) => mentions_self(ty) || mentions_self(err),
+        LangType::Function { params, ret } => params.iter().any(mentions_self) || mentions_self(ret),
+        LangType::Tuple(elems) => elems.iter().any(mentions_self),
+        _ => false
+    }
+}
+
+// behind a vtable the concrete type is unknown, so `Self` may only appear as the receiver
+fn check_object_safe(ctx: &GlobalCtx, trait_name: &str) {
+    let info = ctx.module_traits.borrow().get(trait_name).cloned().unwrap();
+
+    for method in &info.methods {
+        let LangType::Function { params, ret } = &method.ty else {
+            continue;
+        };
+
+        if params.iter().skip(1).any(mentions_self) || mentions_self(ret) {
+            ctx.error(format!("trait {} can not be used as dyn {}: method {} uses Self outside of its receiver", 
+                trait_name, trait_name, method.name));
+        }
+    }
+}
+
+fn check_dyn_types(ctx: &GlobalCtx, ty: &LangType) {
+    match ty {
+        LangType::Dyn(trait_name) => check_object_safe(ctx, trait_name),
+        LangType::StaticArray { ty, .. } | LangType::Slice(ty) | LangType::Vec(ty) | LangType::Option(ty) => check_dyn_types(ctx, ty),
+        LangType::Result(ty, err) => {
+            check_dyn_types(ctx, ty);
+            check_dyn_types(ctx, err);
+        }
+        LangType::Function { params, ret } => {
+            for param in params {
+                check_dyn_types(ctx, param);
+            }
+
+            check_dyn_types(ctx, ret);
+        }
+        LangType::Tuple(elems) => {
+            for elem in elems {
+                check_dyn_types(ctx, elem);
+            }
+        }
+        _ => ()
+    }
+}
+
+fn satisfies_bound(ctx: &GlobalCtx, ty: &LangType, trait_name: &str) -> bool {
+    match ty {
+        LangType::Struct(name) => ctx.module_traits.borrow().implements(name, trait_name),
+        LangType::Param { bounds, .. } => bounds.iter().any(|bound| bound == trait_name),
+        _ => false
+    }
+}
+
+fn check_bounds(ctx: &GlobalCtx, fn_name: &str, ty: &LangType, type_args: &BTreeMap<String, LangType>) {
+    match ty {
+        LangType::Param { name, bounds } => {
+            let arg = &type_args[name];
+
+            // unknown traits were reported by the resolver
+            for bound in bounds.iter().filter(|bound| ctx.module_traits.borrow().contains(bound)) {
+                if !satisfies_bound(ctx, arg, bound) {
+                    ctx.error(format!("type {} does not implement trait {} required by {} of {}", arg, bound, name, fn_name));
+                }
+            }
+        }
+
+        LangType::Function { params, ret } => {
+            for param in params {
+                check_bounds(ctx, fn_name, param, type_args);
+            }
+
+            check_bounds(ctx, fn_name, ret, type_args);
+        }
+
+        LangType::Tuple(elems) => {
+            for elem in elems {
+                check_bounds(ctx, fn_name, elem, type_args);
+            }
+        }
+
+        LangType::StaticArray { ty, .. } | LangType::Slice(ty) | LangType::Vec(ty) | LangType::Option(ty) => {
+            check_bounds(ctx, fn_name, ty, type_args);
+        }
+
+        LangType::Result(ty, err) => {
+            check_bounds(ctx, fn_name, ty, type_args);
+            check_bounds(ctx, fn_name, err, type_args);
+        }
+
+        _ => ()
+    }
+}
+
+// an impl has to provide every method of its trait with the trait's signature, `Self` being the implementing type
+fn check_trait_impl<'a>(ctx: &GlobalCtx<'a>, target: &str, trait_name: &str, methods: &[HirModuleItem<'a>]) {
+    let info = ctx.module_traits.borrow().get(trait_name).cloned().unwrap();
+    let self_map = BTreeMap::from([("Self".to_string(), LangType::Struct(target.to_string()))]);
+
+    for method in methods {
+        let HirModuleItem::Func { id, name, .. } = method else {
+            continue;
+        };
+
+        let Some(expected) = info.method(name) else {
+            ctx.error(format!("method {} is not a member of trait {}", name, trait_name));
+            continue;
+        };
+
+        let expected_ty = expected.ty.substitute(&self_map);
+        let actual_ty = ctx.module_ty_info.borrow().get_type(id).unwrap().ty.clone();
+
+        if differ(&expected_ty, &actual_ty) {
+            ctx.error(format!("method {} of trait {} for {} has type {} but the trait requires {}", 
+                name, trait_name, target, actual_ty, expected_ty));
+        }
+    }
+
+    for expected in &info.methods {
+        let implemented = methods.iter()
+            .any(|method| matches!(method, HirModuleItem::Func { name, .. } if *name == expected.name));
+
+        if !implemented {
+            ctx.error(format!("missing method {} in impl of trait {} for {}", expected.name, trait_name, target));
+        }
+    }
+}
+
+fn pattern_text(pattern: &HirPattern) -> String {
+    match pattern {
+        HirPattern::Binding(name, _) => name.to_string(),
+        HirPattern::Wildcard => "_".to_string(),
+        HirPattern::Tuple(elems) => {
+            let elems: Vec<String> = elems.iter().map(pattern_text).collect();
+            format!("({})", elems.join(", "))
+        }
+        HirPattern::Struct { name, fields } => {
+            let fields: Vec<String> = fields.iter()
+                .map(|(field, pattern)| format!("{}: {}", field, pattern_text(pattern)))
+                .collect();
+            format!("{} {{ {} }}", name, fields.join(", "))
+        }
+    }
+}
+
+fn check_pattern(ctx: &GlobalCtx, pattern: &HirPattern, ty: &LangType) {
+    match (pattern, ty) {
+        (HirPattern::Binding(..) | HirPattern::Wildcard, _) | (_, LangType::Error) => (),
+
+        (HirPattern::Tuple(elems), LangType::Tuple(elem_tys)) if elems.len() == elem_tys.len() => {
+            for (elem, elem_ty) in elems.iter().zip(elem_tys.iter()) {
+                check_pattern(ctx, elem, elem_ty);
+            }
+        }
+
+        (HirPattern::Struct { name, fields }, LangType::Struct(ty_name)) if name == ty_name => {
+            let info = ctx.module_structs.borrow().get(name).cloned().unwrap();
+
+            for (field, field_pattern) in fields {
+                let Some((_, field_ty)) = info.field(field) else {
+                    ctx.error(format!("struct {} has no field {}", name, field));
+                    continue;
+                };
+
+                if fields.iter().filter(|other| other.0 == *field).count() > 1 {
+                    ctx.error(format!("field {} of struct {} is matched more than once", field, name));
+                }
+
+                check_pattern(ctx, field_pattern, field_ty);
+            }
+        }
+
+        _ => ctx.error(format!("pattern {} does not match value of type {}", pattern_text(pattern), ty))
+    }
+}
+
+fn check_builtin_method(ctx: &GlobalCtx, receiver_ty: &LangType, method: &str, args: &[HirExpr]) {
+    match method {
+        "push" => {
+            // other receivers have no push, that was reported when typing the call
+            let LangType::Vec(elem_ty) = receiver_ty else {
+                return;
+            };
+
+            let [arg] = args else {
+                ctx.error(format!("push takes one argument but {} were given", args.len()));
+                return;
+            };
+
+            let arg_ty = value_type(ctx, arg);
+
+            if differ(elem_ty, &arg_ty) {
+                ctx.error(format!("can not push value of type {} to {}", arg_ty, receiver_ty));
+            }
+        }
+
+        _ if !args.is_empty() => ctx.error(format!("{} takes no arguments but {} were given", method, args.len())),
+
+        _ => ()
+    }
+}
+
+fn check_if<'a>(ctx: &GlobalCtx<'a>, expr: &HirExpr<'a>, fn_ret: Option<&LangType>, as_value: bool) {
+    let HirExprKind::If { cond, then, _else } = &expr.kind else {
+        return;
+    };
+
+    check_inner_expressions(ctx, cond, fn_ret);
+    check_inner_expressions(ctx, then, fn_ret);
+
+    let cond_ty = value_type(ctx, cond);
+
+    if differ(&LangType::Primitives(Primitive::Bool), &cond_ty) {
+        ctx.error(format!("condition of if has type {} but has to be Bool", cond_ty));
+    }
+
+    let Some(_else) = _else else {
+        return;
+    };
+
+    check_inner_expressions(ctx, _else, fn_ret);
+
+    if !as_value || diverges(then) || diverges(_else) {
+        return;
+    }
+
+    let then_ty = value_type(ctx, then);
+    let else_ty = value_type(ctx, _else);
+
+    if differ(&then_ty, &else_ty) {
+        ctx.error(format!("else branch of if has type {} but the then branch has type {}", else_ty, then_ty));
+        poison(ctx, expr);
+    }
+}
+
+// declared parameter names of a module function or method, imported functions and closures only carry types
+fn param_names(ctx: &GlobalCtx, callee: HirId) -> Vec<String> {
+    for file in ctx.module_files.iter() {
+        for item in &file.items {
+            let candidates = match item {
+                HirModuleItem::Impl { methods, .. } => methods.iter().collect(),
+                _ => vec![item]
+            };
+
+            for candidate in candidates {
+                if let HirModuleItem::Func { id, args, .. } = candidate && *id == callee {
+                    return args.iter().map(|arg| arg.0.to_string()).collect();
+                }
+            }
+        }
+    }
+
+    Vec::new()
+}
+
+fn check_call_args(ctx: &GlobalCtx, fn_name: &str, names: &[String], params: &[LangType], args: &[HirExpr]) {
+    if params.len() != args.len() {
+        let noun = if params.len() == 1 { "argument" } else { "arguments" };
+        ctx.error(format!("{} takes {} {} but {} were given", fn_name, params.len(), noun, args.len()));
+        return;
+    }
+
+    for (index, (param, arg)) in params.iter().zip(args.iter()).enumerate() {
+        let arg_ty = value_type(ctx, arg);
+
+        if !differ(param, &arg_ty) {
+            continue;
+        }
+
+        // destructured parameters have no name of their own
+        let param_name = match names.get(index) {
+            Some(name) if name != "_" => name.clone(),
+            _ => format!("#{}", index + 1)
+        };
+
+        ctx.error(format!("parameter {} of {} has type {} but the argument has type {}", param_name, fn_name, param, arg_ty));
+    }
+}
+
+pub fn check_inner_expressions<'a>(ctx: &GlobalCtx<'a>, expr: &HirExpr<'a>, fn_ret: Option<&LangType>) {
     match &expr.kind {
         hir::HirExprKind::VarDef { name, value , ty: _} => {
-            let val_ty = ctx.module_ty_info.borrow().get_type(&value.id).unwrap().ty.clone();
+            check_inner_expressions(ctx, value, fn_ret);
+
+            let val_ty = value_type(ctx, value);
             
             let val_expr_ty = ctx.module_ty_info.borrow().get_type(&expr.id).unwrap().ty.clone();
 
-            if val_ty != val_expr_ty {
-                panic!("variable {} has type {:?} but r-value has type {:?}", name, val_expr_ty, val_ty);
+            check_dyn_types(ctx, &val_expr_ty);
+
+            if differ(&val_expr_ty, &val_ty) {
+                ctx.error(format!("variable {} has type {} but r-value has type {}", name, val_expr_ty, val_ty));
+            }
+        }
+
+        HirExprKind::Destructure { pattern, value, ty: _ } => {
+            check_inner_expressions(ctx, value, fn_ret);
+
+            let val_ty = value_type(ctx, value);
+            let pattern_ty = ctx.module_ty_info.borrow().get_type(&expr.id).unwrap().ty.clone();
+
+            check_dyn_types(ctx, &pattern_ty);
+
+            if differ(&pattern_ty, &val_ty) {
+                ctx.error(format!("pattern {} has type {} but r-value has type {}", pattern_text(pattern), pattern_ty, val_ty));
             }
+
+            check_pattern(ctx, pattern, &pattern_ty);
         }
 
         HirExprKind::Binary { op: _, lhs, rhs } => {
-            check_inner_expressions(ctx, lhs);
-            check_inner_expressions(ctx, rhs);
+            check_inner_expressions(ctx, lhs, fn_ret);
+            check_inner_expressions(ctx, rhs, fn_ret);
+        }
+
+        HirExprKind::Call { alias: _, name, args } => {
+            for arg in args {
+                check_inner_expressions(ctx, arg, fn_ret);
+            }
+
+            // a callee that could not be found or called was reported while typing
+            let Some(callee) = ctx.module_symbols.borrow().get_pair(&expr.id).map(|info| info.id) else {
+                return;
+            };
+
+            let callee_ty = ctx.module_ty_info.borrow().get_type(&callee).unwrap().ty.clone();
+
+            let LangType::Function { params, ret: _ } = &callee_ty else {
+                return;
+            };
+
+            let instance = ctx.module_instances.borrow().get_instance(&expr.id).cloned();
+
+            let params: Vec<LangType> = match &instance {
+                Some(type_args) => params.iter().map(|param| param.substitute(type_args)).collect(),
+                None => params.clone()
+            };
 
-            let lhs_ty = ctx.module_ty_info.borrow().get_type(&lhs.id).unwrap().ty.clone();
-            let rhs_ty = ctx.module_ty_info.borrow().get_type(&rhs.id).unwrap().ty.clone();
+            check_call_args(ctx, name, &param_names(ctx, callee), &params, args);
 
-            if lhs_ty != rhs_ty {
-                panic!("In binary operation left hand side has type {:?} but right hand side has type {:?}", lhs_ty, rhs_ty);
+            if let Some(type_args) = &instance {
+                check_bounds(ctx, name, &callee_ty, type_args);
             }
         }
 
-        HirExprKind::Call { name: _, args } => {
+        HirExprKind::If { .. } => check_if(ctx, expr, fn_ret, true),
+
+        // only the last expression gives the block its value, an `if` before it is a statement
+        HirExprKind::Block(exprs) => {
+            for (index, expr) in exprs.iter().enumerate() {
+                if index + 1 < exprs.len() && matches!(expr.kind, HirExprKind::If { .. }) {
+                    check_if(ctx, expr, fn_ret, false);
+                } else {
+                    check_inner_expressions(ctx, expr, fn_ret);
+                }
+            }
+
+            if let Some(last) = exprs.last() && value_type(ctx, last) == LangType::Error {
+                poison(ctx, expr);
+            }
+        }
+
+        HirExprKind::Return(Some(ret_expr)) => {
+            check_inner_expressions(ctx, ret_expr, fn_ret);
+
+            let ret_ty = value_type(ctx, ret_expr);
+
+            if let Some(fn_ret) = fn_ret && differ(fn_ret, &ret_ty) {
+                ctx.error(format!("ret returns a value of type {} from a function returning {}", ret_ty, fn_ret));
+            }
+        }
+
+        HirExprKind::Return(None) => {
+            if let Some(fn_ret) = fn_ret && !fn_ret.is_unit() && !fn_ret.has_error() {
+                ctx.error(format!("ret without a value in a function returning {}", fn_ret));
+            }
+        }
+
+        HirExprKind::Closure { args: _, body } => {
+            check_inner_expressions(ctx, body, None);
+        }
+
+        HirExprKind::MethodCall { receiver, method, args } => {
+            check_inner_expressions(ctx, receiver, fn_ret);
+
             for arg in args {
-                check_inner_expressions(ctx, arg);
+                check_inner_expressions(ctx, arg, fn_ret);
+            }
+
+            let receiver_ty = ctx.module_ty_info.borrow().get_type(&receiver.id).unwrap().ty.clone();
+
+            if receiver_ty.has_builtin_methods() {
+                check_builtin_method(ctx, &receiver_ty, method, args);
+            }
+
+            if let LangType::Struct(ty_name) = &receiver_ty {
+                let Some(method_id) = ctx.module_methods.borrow().get_method(ty_name, method).map(|info| info.id) else {
+                    return;
+                };
+
+                let method_ty = ctx.module_ty_info.borrow().get_type(&method_id).unwrap().ty.clone();
+
+                let LangType::Function { params, ret: _ } = method_ty else {
+                    panic!("{}::{} has non function type {:?}", ty_name, method, method_ty);
+                };
+
+                // the receiver is the first parameter and is passed before the dot
+                let names: Vec<String> = param_names(ctx, method_id).into_iter().skip(1).collect();
+                check_call_args(ctx, &format!("{}::{}", ty_name, method), &names, &params[1..], args);
+            }
+        }
+
+        HirExprKind::VecNew if value_type(ctx, expr) == LangType::Vec(Box::new(LangType::UNRESOLVED)) => {
+            ctx.error("can not infer the element type of Vec::new(), add a type annotation".to_string());
+        }
+
+        HirExprKind::Variant { variant, value } => {
+            if let Some(value) = value {
+                check_inner_expressions(ctx, value, fn_ret);
+            }
+
+            if !value_type(ctx, expr).is_resolved() {
+                ctx.error(format!("can not infer the type of {:?}, add a type annotation", variant));
             }
-            //to do - create fn type, for checking match of fn type with call args
         }
 
-        HirExprKind::If { cond, then, _else } => {
-            check_inner_expressions(ctx, cond);
-            check_inner_expressions(ctx, then);
+        // `?` hands the `None` or the error on to the caller, so the function has to return the same kind of value
+        HirExprKind::Try(inner) => {
+            check_inner_expressions(ctx, inner, fn_ret);
 
-            if _else.is_none() {
+            let inner_ty = value_type(ctx, inner);
+
+            let compatible = match (&inner_ty, fn_ret) {
+                (LangType::Error, _) | (_, Some(LangType::Error)) => true,
+                (LangType::Option(_), Some(LangType::Option(_))) => true,
+                (LangType::Result(_, err), Some(LangType::Result(_, ret_err))) => !differ(ret_err, err),
+                _ => false
+            };
+
+            if !compatible {
+                match fn_ret {
+                    Some(ret) => ctx.error(format!("the ? operator can not propagate a value of type {} out of a function returning {}",
+                        inner_ty, ret)),
+                    None => ctx.error("the ? operator can not be used in closures, constants or deferred expressions".to_string())
+                }
+            }
+        }
+
+        // deferred code runs while the function is already returning, so it can not propagate with `?`
+        HirExprKind::Defer(inner) => {
+            check_inner_expressions(ctx, inner, None);
+        }
+
+        HirExprKind::Array(elems) => {
+            for elem in elems {
+                check_inner_expressions(ctx, elem, fn_ret);
+            }
+
+            let Some(first) = elems.first() else {
+                ctx.error("empty array literal has no element type".to_string());
                 return;
+            };
+
+            let elem_ty = value_type(ctx, first);
+
+            for elem in elems {
+                let ty = value_type(ctx, elem);
+
+                if differ(&elem_ty, &ty) {
+                    ctx.error(format!("array element has type {} but the first element has type {}", ty, elem_ty));
+                }
             }
+        }
+
+        HirExprKind::Index { expr: inner, index } => {
+            check_inner_expressions(ctx, inner, fn_ret);
+            check_inner_expressions(ctx, index, fn_ret);
+
+            let index_ty = ctx.module_ty_info.borrow().get_type(&index.id).unwrap().ty.clone();
 
-            check_inner_expressions(ctx, _else.as_ref().unwrap());
+            if !matches!(&index_ty, LangType::Primitives(p) if p.is_integer()) && !index_ty.has_error() {
+                ctx.error(format!("index has type {} but has to be an integer", index_ty));
+            }
         }
 
-        HirExprKind::Block(exprs) => {
-            for expr in exprs {
-                check_inner_expressions(ctx, expr);
+        HirExprKind::For { pattern, iterable, body } => {
+            check_inner_expressions(ctx, iterable, fn_ret);
+
+            let iterable_ty = ctx.module_ty_info.borrow().get_type(&iterable.id).unwrap().ty.clone();
+
+            match iterable_ty.element_type() {
+                Some(elem_ty) => check_pattern(ctx, pattern, elem_ty),
+                None if iterable_ty.has_error() => (),
+                None => ctx.error(format!("value of type {} can not be iterated", iterable_ty))
+            }
+
+            check_inner_expressions(ctx, body, fn_ret);
+        }
+
+        HirExprKind::FieldAccess { expr: inner, field: _ } => {
+            check_inner_expressions(ctx, inner, fn_ret);
+        }
+
+        HirExprKind::Tuple(elems) => {
+            for elem in elems {
+                check_inner_expressions(ctx, elem, fn_ret);
+            }
+        }
+
+        HirExprKind::TupleIndex { expr: inner, index: _ } => {
+            check_inner_expressions(ctx, inner, fn_ret);
+        }
+
+        HirExprKind::StructLiteral { name, fields } => {
+            for field in fields {
+                check_inner_expressions(ctx, &field.1, fn_ret);
+            }
+
+            // an unknown struct was reported while typing
+            let Some(info) = ctx.module_structs.borrow().get(name).cloned() else {
+                return;
+            };
+
+            for (field_name, value) in fields {
+                let Some((_, field_ty)) = info.field(field_name) else {
+                    ctx.error(format!("struct {} has no field {}", name, field_name));
+                    continue;
+                };
+
+                if fields.iter().filter(|field| field.0 == *field_name).count() > 1 {
+                    ctx.error(format!("field {} of struct {} is initialized more than once", field_name, name));
+                }
+
+                let value_ty = value_type(ctx, value);
+
+                if differ(field_ty, &value_ty) {
+                    ctx.error(format!("field {} of struct {} has type {} but value has type {}", field_name, name, field_ty, value_ty));
+                }
+            }
+
+            for (field_name, _) in &info.fields {
+                if !fields.iter().any(|field| field.0 == *field_name) {
+                    ctx.error(format!("missing field {} in initializer of struct {}", field_name, name));
+                }
+            }
+        }
+
+        HirExprKind::Cast { expr: inner, ty: _ } => {
+            check_inner_expressions(ctx, inner, fn_ret);
+
+            let from_ty = ctx.module_ty_info.borrow().get_type(&inner.id).unwrap().ty.clone();
+            let to_ty = ctx.module_ty_info.borrow().get_type(&expr.id).unwrap().ty.clone();
+
+            if !is_valid_cast(&from_ty, &to_ty) && !from_ty.has_error() && !to_ty.has_error() {
+                ctx.error(format!("Can not cast value of type {} to type {}", from_ty, to_ty));
             }
         }
 
@@ -53,22 +611,97 @@ pub fn check_inner_expressions<'a>(ctx: &GlobalCtx<'a>, expr: &HirExpr<'a>) {
     }
 }
 
-pub fn validate_hir<'a>(ctx: &GlobalCtx<'a>) {
+fn validate_function<'a>(ctx: &GlobalCtx<'a>, item: &HirModuleItem<'a>) {
+    let HirModuleItem::Func { id, name, generics: _, args: _, patterns, body, visibility, ret_ty } = item else {
+        return;
+    };
+
+    let fn_type = ctx.module_ty_info.borrow().get_type(id).unwrap().clone();
+
+    check_dyn_types(ctx, &fn_type.ty);
+
+    let LangType::Function { params, ret } = fn_type.ty else {
+        panic!("function {} has non function type {:?}", name, fn_type.ty);
+    };
+
+    for (index, pattern) in patterns {
+        check_pattern(ctx, pattern, &params[*index]);
+    }
+
+    check_inner_expressions(ctx, body, Some(&ret));
+
+    // every `ret` was checked on its own, so only a body that can run to its end has to produce the value
+    if diverges(body) {
+        return;
+    }
+
+    let body_type = value_type(ctx, body);
+
+    if !differ(&ret, &body_type) {
+        return;
+    }
+
+    if body_type.is_unit() {
+        ctx.error(format!("not all paths of function {} return a value of type {}", name, ret));
+    } else if ret_ty.is_none() && matches!(visibility, HirVisibility::Public) {
+        ctx.error(format!("public function {} has to declare its return type, its body has type {}", name, body_type));
+    } else {
+        ctx.error(format!("function {} has type {} but body has type {}", name, ret, body_type));
+    }
+}
+
+fn check_items<'a>(ctx: &GlobalCtx<'a>) {
     for file in ctx.module_files.iter() {
         for decl in &file.items {
             match decl {
-                HirModuleItem::Func { id, name, args: _, body, visibility: _, ret_ty: _ } => {
-                    let fn_type = ctx.module_ty_info.borrow().get_type(&id).unwrap().clone();
+                HirModuleItem::Func { .. } => validate_function(ctx, decl),
+
+                HirModuleItem::Const { id, name, value, .. } => {
+                    let const_type = ctx.module_ty_info.borrow().get_type(&id).unwrap().clone();
 
-                    let body_type = ctx.module_ty_info.borrow().get_type(&body.id).unwrap().clone();
+                    let value_type = value_type(ctx, value);
 
-                    if fn_type.ty != body_type.ty {
-                        panic!("function {} has type {:?} but body has type {:?}", name, fn_type.ty, body_type.ty);
+                    if differ(&const_type.ty, &value_type) {
+                        ctx.error(format!("constant {} has type {} but value has type {}", name, const_type.ty, value_type));
                     }
 
-                    check_inner_expressions(ctx, body);
+                    check_inner_expressions(ctx, value, None);
+                }
+
+                HirModuleItem::Struct { name, .. } => {
+                    let info = ctx.module_structs.borrow().get(name).cloned().unwrap();
+
+                    for (_, field_ty) in &info.fields {
+                        check_dyn_types(ctx, field_ty);
+                    }
+                }
+
+                HirModuleItem::Trait { .. } => (),
+
+                HirModuleItem::TypeAlias { name, .. } => {
+                    let info = ctx.module_type_aliases.borrow().get(name).cloned().unwrap();
+                    check_dyn_types(ctx, &info.ty);
+                }
+
+                HirModuleItem::Impl { id: _, target, trait_name, methods } => {
+                    if let Some(trait_name) = trait_name {
+                        check_trait_impl(ctx, target, trait_name, methods);
+                    }
+
+                    for method in methods {
+                        validate_function(ctx, method);
+                    }
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
+
+pub fn validate_hir<'a>(ctx: &GlobalCtx<'a>) {
+    check_items(ctx);
+
+    ctx.abort_on_errors();
+}
+
+#[cfg(test)]
+mod tests;
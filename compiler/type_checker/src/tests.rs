@@ -0,0 +1,416 @@
+use ast_lowering::translate_to_hir;
+use hir_resolver::resolve_module;
+use middle::{BuildType, GlobalCtx, ModuleInterface};
+use parser::parse_file;
+use types_lowering::type_hir_module;
+
+use crate::check_items;
+
+// the errors reported by all passes up to and including the checker
+fn check_source(source: &str) -> Vec<String> {
+    check_with_imports(source, Vec::new())
+}
+
+fn check_with_imports(source: &str, interfaces: Vec<ModuleInterface>) -> Vec<String> {
+    let parsed = parse_file("test", source);
+
+    let mut ctx = GlobalCtx::new("test".to_string(), "x86_64".to_string(), BuildType::Executable);
+    ctx.module_interfaces = interfaces;
+    let file = translate_to_hir(&ctx, &parsed);
+    ctx.module_files.push(file);
+
+    resolve_module(&mut ctx);
+    type_hir_module(&mut ctx);
+    check_items(&ctx);
+
+    ctx.module_errors.take()
+}
+
+fn interface_of(name: &str, source: &str) -> ModuleInterface {
+    let parsed = parse_file(name, source);
+
+    let mut ctx = GlobalCtx::new(name.to_string(), "x86_64".to_string(), BuildType::ModulePack);
+    let file = translate_to_hir(&ctx, &parsed);
+    ctx.module_files.push(file);
+
+    resolve_module(&mut ctx);
+    type_hir_module(&mut ctx);
+    check_items(&ctx);
+    assert_eq!(ctx.module_errors.take(), Vec::<String>::new());
+
+    ctx.interface()
+}
+
+#[test]
+fn checks_pushed_values_against_the_element_type() {
+    assert_eq!(
+        check_source("fn main() -> Int { val v: Vec<Int> = Vec::new(); v.push(true); ret 0; }"),
+        vec!["can not push value of type Bool to Vec<Int>"]
+    );
+}
+
+#[test]
+fn reports_push_on_other_types_once() {
+    assert_eq!(
+        check_source("fn main() -> Int { val o = Some(1); o.push(2); ret 0; }"),
+        vec!["no method push found for type Option<Int>"]
+    );
+}
+
+#[test]
+fn allows_push_through_any_vector_expression() {
+    let source = "
+        struct Bag { items: Vec<Int> }
+
+        fn main() -> Int {
+            val bag = Bag { items: Vec::new() };
+            bag.items.push(1);
+            ret bag.items.len();
+        }
+    ";
+
+    assert_eq!(check_source(source), Vec::<String>::new());
+}
+
+const SHAPES: &str = "
+    pub trait Area {
+        fn area(self) -> Int;
+    }
+    trait Hidden {
+        fn secret(self) -> Int;
+    }
+    pub struct Square { side: Int }
+    impl Area for Square { fn area(self) -> Int { ret self.side * self.side; } }
+";
+
+#[test]
+fn exports_only_public_traits_and_impls() {
+    let interface = interface_of("shapes", SHAPES);
+
+    let traits: Vec<&str> = interface.traits.iter().map(|module_trait| module_trait.name.as_str()).collect();
+    assert_eq!(traits, ["Area"]);
+    assert_eq!(interface.impls, [("Square".to_string(), "Area".to_string())]);
+}
+
+#[test]
+fn implements_traits_of_imported_modules() {
+    let source = "
+        import shapes
+        struct Circle { r: Int }
+        impl Area for Circle { fn area(self) -> Int { ret 3 * self.r * self.r; } }
+        fn total<T: Area>(shape: T) -> Int { ret shape.area(); }
+        fn main() -> Int { ret total(Circle { r: 1 }); }
+    ";
+
+    assert_eq!(check_with_imports(source, vec![interface_of("shapes", SHAPES)]), Vec::<String>::new());
+}
+
+#[test]
+fn checks_impls_of_imported_traits() {
+    let source = "
+        import shapes
+        struct Circle { r: Int }
+        impl Area for Circle { fn perimeter(self) -> Int { ret 6 * self.r; } }
+        impl Hidden for Circle { fn secret(self) -> Int { ret 0; } }
+        fn main() -> Int { ret 0; }
+    ";
+
+    assert_eq!(check_with_imports(source, vec![interface_of("shapes", SHAPES)]), vec![
+        "impl of unknown trait Hidden for Circle",
+        "method perimeter is not a member of trait Area",
+        "missing method area in impl of trait Area for Circle"
+    ]);
+}
+
+#[test]
+fn accepts_bodies_that_return_from_nested_branches() {
+    let source = "
+        fn sign(x: Int) -> Int {
+            if x > 0 {
+                ret 1;
+            } else {
+                if x < 0 { ret 0 - 1; } else { ret 0; }
+            }
+        }
+        fn main() -> Int { ret sign(2); }
+    ";
+
+    assert_eq!(check_source(source), Vec::<String>::new());
+}
+
+#[test]
+fn types_if_by_the_branch_that_does_not_return() {
+    let source = "
+        fn pick(x: Int) -> Int {
+            if x > 0 { x; } else { if x < 0 { ret 1; } else { ret 2; } }
+        }
+        fn main() -> Int { ret pick(2); }
+    ";
+
+    assert_eq!(check_source(source), Vec::<String>::new());
+}
+
+#[test]
+fn reports_bodies_that_miss_a_ret_on_some_path() {
+    let source = "
+        fn half(x: Int) -> Int {
+            if x > 0 { ret x / 2; }
+        }
+        fn main() -> Int { ret half(2); }
+    ";
+
+    assert_eq!(check_source(source), vec!["not all paths of function half return a value of type Int"]);
+}
+
+#[test]
+fn accepts_casts_between_numbers_chars_and_bools() {
+    let source = "
+        fn main() -> Int {
+            val small = 300 as U8;
+            val wide = small as I64;
+            val float = wide as F32;
+            val back = float as Int;
+            val letter = 98 as Char;
+            val code = letter as U32;
+            val flag = true as Int;
+            ret back + code as Int + flag;
+        }
+    ";
+
+    assert_eq!(check_source(source), Vec::<String>::new());
+}
+
+#[test]
+fn rejects_casts_outside_the_cast_table() {
+    let source = "
+        struct Point { x: Int }
+        fn main() -> Int {
+            val flag = 1 as Bool;
+            val half = 0.5 as Bool;
+            val point = Point { x: 1 };
+            ret point as Int;
+        }
+    ";
+
+    assert_eq!(check_source(source), vec![
+        "Can not cast value of type Int to type Bool",
+        "Can not cast value of type Float to type Bool",
+        "Can not cast value of type Point to type Int"
+    ]);
+}
+
+#[test]
+fn reports_calls_with_the_wrong_number_of_arguments() {
+    let source = "
+        fn add(a: Int, b: Int) -> Int { ret a + b; }
+        fn twice(a: Int) -> Int { ret a * 2; }
+        fn main() -> Int { ret add(1) + twice(1, 2) + add(1, 2); }
+    ";
+
+    assert_eq!(check_source(source), vec![
+        "add takes 2 arguments but 1 were given",
+        "twice takes 1 argument but 2 were given"
+    ]);
+}
+
+#[test]
+fn reports_arguments_of_the_wrong_type_by_parameter() {
+    let source = "
+        fn scale(value: Float, times: Int) -> Float { ret value * times as Float; }
+        fn main() -> Int {
+            val scaled = scale(2, true);
+            ret 0;
+        }
+    ";
+
+    assert_eq!(check_source(source), vec![
+        "parameter value of scale has type Float but the argument has type Int",
+        "parameter times of scale has type Int but the argument has type Bool"
+    ]);
+}
+
+#[test]
+fn checks_method_arguments_after_the_receiver() {
+    let source = "
+        trait Grow {
+            fn grow(self, by: Int) -> Int;
+        }
+        struct Plant { height: Int }
+        impl Grow for Plant { fn grow(self, by: Int) -> Int { ret self.height + by; } }
+        fn main() -> Int {
+            val plant = Plant { height: 1 };
+            ret plant.grow() + plant.grow(false) + plant.grow(2);
+        }
+    ";
+
+    assert_eq!(check_source(source), vec![
+        "Plant::grow takes 1 argument but 0 were given",
+        "parameter by of Plant::grow has type Int but the argument has type Bool"
+    ]);
+}
+
+#[test]
+fn checks_every_ret_against_the_return_type() {
+    let source = "
+        fn check(x: Int) -> Int {
+            if x > 0 { ret true; }
+            if x < 0 { ret; }
+            ret x;
+        }
+        fn main() -> Int { ret check(1); }
+    ";
+
+    assert_eq!(check_source(source), vec![
+        "ret returns a value of type Bool from a function returning Int",
+        "ret without a value in a function returning Int"
+    ]);
+}
+
+#[test]
+fn reports_branches_of_different_types() {
+    let source = "
+        fn pick(x: Int) -> Int {
+            if x > 0 { 1; } else { false; }
+        }
+        fn main() -> Int { ret pick(1); }
+    ";
+
+    assert_eq!(check_source(source), vec!["else branch of if has type Bool but the then branch has type Int"]);
+}
+
+#[test]
+fn types_uses_of_parameters_by_the_parameter() {
+    let source = "
+        fn choose(flag: Bool, value: Float) -> Int {
+            if flag { ret value as Int; }
+            ret 0;
+        }
+        fn count(count: Int) -> Bool { ret count > 0; }
+        fn main() -> Int { ret choose(count(1), 2.5); }
+    ";
+
+    assert_eq!(check_source(source), Vec::<String>::new());
+}
+
+#[test]
+fn accepts_dyn_traits_that_use_self_only_as_receiver() {
+    let source = "
+        trait Shape {
+            fn area(self) -> Int;
+        }
+        struct Square { side: Int }
+        impl Shape for Square { fn area(self) -> Int { ret self.side * self.side; } }
+        fn total(shape: dyn Shape) -> Int { ret shape.area(); }
+        fn main() -> Int {
+            val shape: dyn Shape = Square { side: 2 };
+            ret total(shape);
+        }
+    ";
+
+    assert_eq!(check_source(source), Vec::<String>::new());
+}
+
+#[test]
+fn rejects_dyn_traits_that_use_self_outside_the_receiver() {
+    let source = "
+        trait Same {
+            fn same(self, other: Self) -> Bool;
+        }
+        trait Copy {
+            fn copy(self) -> Self;
+        }
+        struct Holder { same: dyn Same }
+        fn copy_of(value: dyn Copy) -> Int { ret 0; }
+        fn main() -> Int { ret 0; }
+    ";
+
+    assert_eq!(check_source(source), vec![
+        "trait Same can not be used as dyn Same: method same uses Self outside of its receiver",
+        "trait Copy can not be used as dyn Copy: method copy uses Self outside of its receiver"
+    ]);
+}
+
+#[test]
+fn checks_local_impls_against_their_trait() {
+    let source = "
+        trait Shape {
+            fn area(self) -> Int;
+            fn scaled(self, by: Int) -> Self;
+        }
+        struct Square { side: Int }
+        struct Circle { r: Int }
+        impl Shape for Square {
+            fn area(self) -> Int { ret self.side * self.side; }
+            fn scaled(self, by: Int) -> Square { ret Square { side: self.side * by }; }
+        }
+        impl Shape for Circle {
+            fn area(self) -> Float { ret 3.0; }
+            fn radius(self) -> Int { ret self.r; }
+        }
+        fn main() -> Int { ret 0; }
+    ";
+
+    assert_eq!(check_source(source), vec![
+        "method area of trait Shape for Circle has type fn(Circle) -> Float but the trait requires fn(Circle) -> Int",
+        "method radius is not a member of trait Shape",
+        "missing method scaled in impl of trait Shape for Circle"
+    ]);
+}
+
+#[test]
+fn propagates_with_question_mark_into_compatible_returns() {
+    let source = "
+        fn parse(n: Int) -> Result<Int, Bool> {
+            if n < 0 { ret Err(false); }
+            ret Ok(n);
+        }
+        fn find(n: Int) -> Option<Int> {
+            if n < 0 { ret None; }
+            ret Some(n);
+        }
+        fn twice(n: Int) -> Result<Float, Bool> {
+            val a = parse(n)?;
+            ret Ok(a as Float * 2.0);
+        }
+        fn first(n: Int) -> Option<Bool> {
+            val a = find(n)?;
+            ret Some(a > 0);
+        }
+        fn main() -> Int { ret 0; }
+    ";
+
+    assert_eq!(check_source(source), Vec::<String>::new());
+}
+
+#[test]
+fn reports_question_mark_in_incompatible_returns() {
+    let source = "
+        fn parse(n: Int) -> Result<Int, Bool> {
+            if n < 0 { ret Err(false); }
+            ret Ok(n);
+        }
+        fn find(n: Int) -> Option<Int> {
+            if n < 0 { ret None; }
+            ret Some(n);
+        }
+        fn other_error(n: Int) -> Result<Int, Int> {
+            val a = parse(n)?;
+            ret Ok(a);
+        }
+        fn plain(n: Int) -> Int {
+            val a = find(n)?;
+            ret a;
+        }
+        fn mixed(n: Int) -> Option<Int> {
+            val a = parse(n)?;
+            ret Some(a);
+        }
+        fn main() -> Int { ret 0; }
+    ";
+
+    assert_eq!(check_source(source), vec![
+        "the ? operator can not propagate a value of type Result<Int, Bool> out of a function returning Result<Int, Int>",
+        "the ? operator can not propagate a value of type Option<Int> out of a function returning Int",
+        "the ? operator can not propagate a value of type Result<Int, Bool> out of a function returning Option<Int>"
+    ]);
+}
@@ -1,34 +1,464 @@
-use hir::{HirExpr, HirExprKind, HirModuleItem, HirTyHint};
-use middle::{ty::{LangType, Primitive}, GlobalCtx, TypeInfo};
+use std::collections::BTreeMap;
 
-fn translate_hint_to_type<'a>(hint: &HirTyHint<'a>) -> LangType {
+use hir::{diverges, HirBinOps, HirExpr, HirExprKind, HirId, HirModuleItem, HirPattern, HirTyHint, HirVariant, HirVisibility};
+use middle::{ty::{LangType, Primitive}, GlobalCtx, SymbolInfo, SymbolKind, TraitMethodInfo, TypeInfo};
+
+fn translate_hint_to_type<'a>(ctx: &GlobalCtx<'a>, hint: &HirTyHint<'a>) -> LangType {
     match hint {
         HirTyHint::Primitive(hint) => {
-            match *hint {
-                "Bool" => LangType::Primitives(Primitive::Bool),
-                "Int" => LangType::Primitives(Primitive::Int),
-                "Float" => LangType::Primitives(Primitive::Float),
-                "Char" => LangType::Primitives(Primitive::Char),
-                _ => LangType::UNRESOLVED  
+            match Primitive::from_name(hint) {
+                Some(primitive) => LangType::Primitives(primitive),
+                None if ctx.module_structs.borrow().contains(hint) => LangType::Struct(hint.to_string()),
+                // the resolver reported the unknown name
+                None => alias_type(ctx, hint).unwrap_or(LangType::Error)
+            }
+        },
+        HirTyHint::Function(params, ret) => LangType::Function {
+            params: params.iter().map(|param| translate_hint_to_type(ctx, param)).collect(),
+            ret: Box::new(ret.as_ref()
+                .map(|ret| translate_hint_to_type(ctx, ret))
+                .unwrap_or(LangType::Primitives(Primitive::Unit)))
+        },
+        HirTyHint::Dyn(trait_name) => {
+            if !ctx.module_traits.borrow().contains(trait_name) {
+                ctx.error(format!("unknown trait {} in dyn {}", trait_name, trait_name));
+                return LangType::Error;
+            }
+
+            LangType::Dyn(trait_name.to_string())
+        },
+        HirTyHint::Generic(name, bounds) => LangType::Param {
+            name: name.to_string(),
+            bounds: bounds.iter().map(|bound| bound.to_string()).collect()
+        },
+        HirTyHint::Tuple(elems) => LangType::Tuple(
+            elems.iter().map(|elem| translate_hint_to_type(ctx, elem)).collect()
+        ),
+        HirTyHint::Array(elem, size) => LangType::StaticArray {
+            size: *size as u64,
+            ty: Box::new(translate_hint_to_type(ctx, elem))
+        },
+        HirTyHint::Slice(elem) => LangType::Slice(Box::new(translate_hint_to_type(ctx, elem))),
+        HirTyHint::Applied("Vec", args) => match args.as_slice() {
+            [elem] => LangType::Vec(Box::new(translate_hint_to_type(ctx, elem))),
+            _ => {
+                ctx.error(format!("Vec takes one type argument but {} were given", args.len()));
+                LangType::Error
+            }
+        },
+        HirTyHint::Applied("Option", args) => match args.as_slice() {
+            [ty] => LangType::Option(Box::new(translate_hint_to_type(ctx, ty))),
+            _ => {
+                ctx.error(format!("Option takes one type argument but {} were given", args.len()));
+                LangType::Error
             }
         },
-        _ => LangType::UNRESOLVED,
+        HirTyHint::Applied("Result", args) => match args.as_slice() {
+            [ty, err] => LangType::Result(Box::new(translate_hint_to_type(ctx, ty)), Box::new(translate_hint_to_type(ctx, err))),
+            _ => {
+                ctx.error(format!("Result takes two type arguments but {} were given", args.len()));
+                LangType::Error
+            }
+        },
+        // the resolver reported the unknown name
+        HirTyHint::Applied(..) => LangType::Error
+    }
+}
+
+// aliases are resolved on their first use, as they may refer to ones declared after them
+fn alias_type(ctx: &GlobalCtx, name: &str) -> Option<LangType> {
+    let mut info = ctx.module_type_aliases.borrow().get(name).cloned()?;
+
+    if info.ty == LangType::UNRESOLVED {
+        let hint = ctx.module_files.iter()
+            .flat_map(|file| file.items.iter())
+            .find_map(|item| match item {
+                HirModuleItem::TypeAlias { name: alias, ty, .. } if *alias == name => Some(ty),
+                _ => None
+            })
+            .unwrap();
+
+        info.ty = translate_hint_to_type(ctx, hint);
+        ctx.module_type_aliases.borrow_mut().set_type(name, info.ty.clone());
     }
+
+    if info.distinct {
+        return Some(LangType::Newtype { name: name.to_string(), ty: Box::new(info.ty) });
+    }
+
+    Some(info.ty)
 }
 
-fn infer_expr<'a>(ctx: &GlobalCtx<'a>, expr: &HirExpr) {
+// binds generic parameters of a callee signature to the types of the passed arguments
+fn infer_type_args(ctx: &GlobalCtx, fn_name: &str, param: &LangType, arg: &LangType, map: &mut BTreeMap<String, LangType>) {
+    match (param, arg) {
+        (LangType::Param { name, .. }, _) => {
+            if let Some(bound) = map.get(name) && bound != arg {
+                ctx.error(format!("type parameter {} of {} is used with types {} and {}", name, fn_name, bound, arg));
+                return;
+            }
+
+            map.insert(name.clone(), arg.clone());
+        }
+
+        (LangType::Function { params, ret }, LangType::Function { params: arg_params, ret: arg_ret }) => {
+            for (param, arg) in params.iter().zip(arg_params.iter()) {
+                infer_type_args(ctx, fn_name, param, arg, map);
+            }
+
+            infer_type_args(ctx, fn_name, ret, arg_ret, map);
+        }
+
+        (LangType::Tuple(elems), LangType::Tuple(arg_elems)) => {
+            for (elem, arg) in elems.iter().zip(arg_elems.iter()) {
+                infer_type_args(ctx, fn_name, elem, arg, map);
+            }
+        }
+
+        (LangType::StaticArray { ty, .. }, LangType::StaticArray { ty: arg_ty, .. })
+        | (LangType::Slice(ty), LangType::Slice(arg_ty))
+        | (LangType::Vec(ty), LangType::Vec(arg_ty))
+        | (LangType::Option(ty), LangType::Option(arg_ty)) => infer_type_args(ctx, fn_name, ty, arg_ty, map),
+
+        (LangType::Result(ty, err), LangType::Result(arg_ty, arg_err)) => {
+            infer_type_args(ctx, fn_name, ty, arg_ty, map);
+            infer_type_args(ctx, fn_name, err, arg_err, map);
+        }
+
+        _ => ()
+    }
+}
+
+fn collect_type_params(ty: &LangType, names: &mut Vec<String>) {
+    match ty {
+        LangType::Param { name, .. } if !names.contains(name) => names.push(name.clone()),
+        LangType::StaticArray { ty, .. } | LangType::Slice(ty) | LangType::Vec(ty) | LangType::Option(ty) => collect_type_params(ty, names),
+        LangType::Result(ty, err) => {
+            collect_type_params(ty, names);
+            collect_type_params(err, names);
+        }
+        LangType::Function { params, ret } => {
+            for param in params {
+                collect_type_params(param, names);
+            }
+
+            collect_type_params(ret, names);
+        }
+        LangType::Tuple(elems) => {
+            for elem in elems {
+                collect_type_params(elem, names);
+            }
+        }
+        _ => ()
+    }
+}
+
+// literals without a suffix, and arithmetic on nothing else, take their type from the place they are used in
+fn is_untyped_literal(expr: &HirExpr) -> bool {
+    match &expr.kind {
+        HirExprKind::Int(_, _, None) | HirExprKind::Float(_, None) => true,
+        HirExprKind::Binary { op: HirBinOps::LT | HirBinOps::GT | HirBinOps::EQ, .. } => false,
+        HirExprKind::Binary { lhs, rhs, .. } => is_untyped_literal(lhs) && is_untyped_literal(rhs),
+        _ => false
+    }
+}
+
+fn check_literal_range(ctx: &GlobalCtx, expr: &HirExpr, primitive: &Primitive) {
+    match &expr.kind {
+        HirExprKind::Int(val, negative, _) if !primitive.fits_literal(*val, *negative) =>
+            ctx.error(format!("{}{} does not fit in {}", if *negative { "-" } else { "" }, val, primitive.name())),
+        HirExprKind::Float(val, _) if !primitive.fits_float(*val) =>
+            ctx.error(format!("{} does not fit in {}", val, primitive.name())),
+        _ => ()
+    }
+}
+
+fn coerce_to(ctx: &GlobalCtx, expr: &HirExpr, target: &LangType) {
+    if is_untyped_literal(expr) && let LangType::Primitives(primitive) = target.underlying() {
+        let is_number = match &expr.kind {
+            HirExprKind::Float(..) => primitive.is_float(),
+            _ => primitive.is_integer() && *primitive != Primitive::Char
+        };
+
+        if !is_number {
+            return;
+        }
+
+        if let HirExprKind::Binary { lhs, rhs, .. } = &expr.kind {
+            coerce_to(ctx, lhs, target);
+            coerce_to(ctx, rhs, target);
+        }
+
+        // the literal was already checked against the type it had so far
+        let typed = ctx.module_ty_info.borrow().get_type(&expr.id).is_some_and(|info| info.ty == *target);
+
+        if !typed {
+            check_literal_range(ctx, expr, primitive);
+        }
+
+        ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: target.clone() });
+        return;
+    }
+
+    // a block is converted through the expression that produces its value
+    if let HirExprKind::Block(exprs) = &expr.kind {
+        if let Some(last) = exprs.last() {
+            coerce_to(ctx, last, target);
+
+            let ty = coerced_type(ctx, last);
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty });
+        }
+
+        return;
+    }
+
+    // and an `if` through both of its branches
+    if let HirExprKind::If { cond: _, then, _else: Some(_else) } = &expr.kind {
+        coerce_to(ctx, then, target);
+        coerce_to(ctx, _else, target);
+
+        let then_ty = coerced_type(ctx, then);
+        let else_ty = coerced_type(ctx, _else);
+
+        if then_ty == else_ty || diverges(_else) {
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: then_ty });
+        } else if diverges(then) {
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: else_ty });
+        }
+
+        return;
+    }
+
+    // array literals, tuples and variants are converted part by part and take the types their parts end up with
+    match (&expr.kind, target) {
+        (HirExprKind::Array(elems), LangType::StaticArray { ty: elem_ty, .. } | LangType::Slice(elem_ty)) if !elems.is_empty() => {
+            for elem in elems {
+                coerce_to(ctx, elem, elem_ty);
+            }
+
+            let ty = LangType::StaticArray { size: elems.len() as u64, ty: Box::new(coerced_type(ctx, &elems[0])) };
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty });
+        }
+
+        (HirExprKind::Tuple(elems), LangType::Tuple(elem_tys)) if elems.len() == elem_tys.len() => {
+            for (elem, elem_ty) in elems.iter().zip(elem_tys.iter()) {
+                coerce_to(ctx, elem, elem_ty);
+            }
+
+            let ty = LangType::Tuple(elems.iter().map(|elem| coerced_type(ctx, elem)).collect());
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty });
+        }
+
+        (HirExprKind::Variant { variant, value: Some(value) }, LangType::Option(payload_ty) | LangType::Result(payload_ty, _))
+            if matches!(variant, HirVariant::Some | HirVariant::Ok) => {
+            coerce_to(ctx, value, payload_ty);
+
+            let payload = Box::new(coerced_type(ctx, value));
+            let ty = match variant {
+                HirVariant::Some => LangType::Option(payload),
+                _ => LangType::Result(payload, Box::new(LangType::UNRESOLVED))
+            };
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty });
+        }
+
+        (HirExprKind::Variant { variant: HirVariant::Err, value: Some(value) }, LangType::Result(_, err_ty)) => {
+            coerce_to(ctx, value, err_ty);
+
+            let ty = LangType::Result(Box::new(LangType::UNRESOLVED), Box::new(coerced_type(ctx, value)));
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty });
+        }
+
+        _ => ()
+    }
+
+    let ty = ctx.module_ty_info.borrow().get_type(&expr.id).unwrap().ty.clone();
+
+    let coercible = match (target, &ty) {
+        // a value whose type implements the trait may be used where `dyn Trait` is expected
+        (LangType::Dyn(trait_name), LangType::Struct(name)) => ctx.module_traits.borrow().implements(name, trait_name),
+        (LangType::Dyn(trait_name), LangType::Param { bounds, .. }) => bounds.contains(trait_name),
+
+        // arrays and vectors can be viewed as slices of their elements
+        (LangType::Slice(elem), LangType::StaticArray { ty, .. } | LangType::Vec(ty)) => elem == ty,
+
+        // `Vec::new()`, `None`, `Ok` and `Err` take their missing types from the place they are stored into
+        (_, LangType::UNRESOLVED) => false,
+        _ => ty != *target && ty.is_refined_by(target),
+    };
+
+    if coercible {
+        ctx.module_coercions.borrow_mut().add_coercion(expr.id, target.clone());
+    }
+}
+
+fn coerced_type(ctx: &GlobalCtx, expr: &HirExpr) -> LangType {
+    match ctx.module_coercions.borrow().get_coercion(&expr.id) {
+        Some(target) => target.clone(),
+        None => ctx.module_ty_info.borrow().get_type(&expr.id).unwrap().ty.clone()
+    }
+}
+
+// methods every array, slice, vector, option and result has
+fn builtin_method_type(ctx: &GlobalCtx, receiver_ty: &LangType, method: &str, args: &[HirExpr]) -> LangType {
+    match (receiver_ty, method) {
+        (_, "len") if receiver_ty.element_type().is_some() => LangType::Primitives(Primitive::Int),
+
+        (LangType::Option(_), "is_some" | "is_none")
+        | (LangType::Result(..), "is_ok" | "is_err") => LangType::Primitives(Primitive::Bool),
+
+        (LangType::Option(ty) | LangType::Result(ty, _), "unwrap") => *ty.clone(),
+        (LangType::Result(_, err), "unwrap_err") => *err.clone(),
+
+        (LangType::Vec(elem), "push") => {
+            if let Some(arg) = args.first() {
+                coerce_to(ctx, arg, elem);
+            }
+
+            LangType::Primitives(Primitive::Unit)
+        }
+
+        _ => {
+            ctx.error(format!("no method {} found for type {}", method, receiver_ty));
+            LangType::Error
+        }
+    }
+}
+
+// on a generic receiver only the methods of the traits in its bounds are callable
+fn trait_method_type(ctx: &GlobalCtx, param: &str, bounds: &[String], method: &str) -> LangType {
+    let traits = ctx.module_traits.borrow();
+
+    let candidates: Vec<&TraitMethodInfo> = bounds.iter()
+        .filter_map(|bound| traits.get(bound).and_then(|info| info.method(method)))
+        .collect();
+
+    match candidates.as_slice() {
+        [found] => found.ty.clone(),
+        [] => {
+            ctx.error(format!("no method {} found in the bounds of {}", method, param));
+            LangType::Error
+        }
+        _ => {
+            ctx.error(format!("method {} is provided by more than one bound of {}", method, param));
+            LangType::Error
+        }
+    }
+}
+
+// bindings get the type of the part of the value they match, a pattern that does not fit
+// the value leaves them unresolved and is reported by the checker
+fn type_pattern(ctx: &GlobalCtx, pattern: &HirPattern, ty: &LangType) {
+    let missing = if *ty == LangType::Error { LangType::Error } else { LangType::UNRESOLVED };
+
+    match pattern {
+        HirPattern::Binding(_, id) => {
+            ctx.module_ty_info.borrow_mut().insert_type(*id, TypeInfo { ty: ty.clone() });
+        }
+
+        HirPattern::Wildcard => (),
+
+        HirPattern::Tuple(elems) => {
+            for (index, elem) in elems.iter().enumerate() {
+                let elem_ty = match ty {
+                    LangType::Tuple(elem_tys) => elem_tys.get(index).cloned(),
+                    _ => None
+                };
+
+                type_pattern(ctx, elem, &elem_ty.unwrap_or(missing.clone()));
+            }
+        }
+
+        HirPattern::Struct { name: _, fields } => {
+            for (field, field_pattern) in fields {
+                let field_ty = match ty {
+                    LangType::Struct(ty_name) => ctx.module_structs.borrow().get(ty_name)
+                        .and_then(|info| info.field(field).map(|(_, field_ty)| field_ty.clone())),
+                    _ => None
+                };
+
+                type_pattern(ctx, field_pattern, &field_ty.unwrap_or(missing.clone()));
+            }
+        }
+    }
+}
+
+// the lexer only lets known suffixes through
+fn literal_primitive(suffix: Option<&str>, default: Primitive) -> Primitive {
+    suffix.and_then(Primitive::from_suffix).unwrap_or(default)
+}
+
+fn op_text(op: &HirBinOps) -> &'static str {
+    match op {
+        HirBinOps::SUM => "+",
+        HirBinOps::SUB => "-",
+        HirBinOps::MUL => "*",
+        HirBinOps::DIV => "/",
+        HirBinOps::AND => "&&",
+        HirBinOps::OR => "||",
+        HirBinOps::LT => "<",
+        HirBinOps::GT => ">",
+        HirBinOps::EQ => "==",
+        HirBinOps::SHL => "<<",
+        HirBinOps::SHR => ">>"
+    }
+}
+
+// arithmetic and ordering need numbers, shifts integers, logic Bools and equality any primitive, always on both sides alike
+fn check_binary_operands(ctx: &GlobalCtx, op: &HirBinOps, lhs_ty: &LangType, rhs_ty: &LangType) -> bool {
+    let LangType::Primitives(p) = lhs_ty.underlying() else {
+        ctx.error(format!("operator {} can not be applied to values of type {}", op_text(op), lhs_ty));
+        return false;
+    };
+
+    let supported = match op {
+        HirBinOps::SUM | HirBinOps::SUB | HirBinOps::MUL | HirBinOps::DIV | HirBinOps::LT | HirBinOps::GT => p.is_integer() || p.is_float(),
+        HirBinOps::SHL | HirBinOps::SHR => p.is_integer(),
+        HirBinOps::AND | HirBinOps::OR => *p == Primitive::Bool,
+        HirBinOps::EQ => *p != Primitive::Unit
+    };
+
+    if !supported {
+        ctx.error(format!("operator {} can not be applied to values of type {}", op_text(op), lhs_ty));
+        return false;
+    }
+
+    if lhs_ty != rhs_ty {
+        ctx.error(format!("operator {} has operands of different types: {} and {}", op_text(op), lhs_ty, rhs_ty));
+        return false;
+    }
+
+    true
+}
+
+// an expression whose error was reported gets the error type, so whatever uses it is not reported again
+fn type_error(ctx: &GlobalCtx, expr: &HirExpr, message: String) {
+    ctx.error(message);
+    poison(ctx, expr);
+}
+
+fn poison(ctx: &GlobalCtx, expr: &HirExpr) {
+    ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: LangType::Error });
+}
+
+fn infer_expr<'a>(ctx: &GlobalCtx<'a>, expr: &HirExpr<'a>, fn_ret: Option<&LangType>) {
     match &expr.kind {
         HirExprKind::Bool(_) => ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo {
             ty: LangType::Primitives(middle::ty::Primitive::Bool),
         }),
 
-        HirExprKind::Int(_) => ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo {
-            ty: LangType::Primitives(middle::ty::Primitive::Int),
-        }),
+        HirExprKind::Int(_, _, suffix) => {
+            let primitive = literal_primitive(*suffix, Primitive::Int);
+            check_literal_range(ctx, expr, &primitive);
 
-        HirExprKind::Float(_) => ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo {
-            ty: LangType::Primitives(middle::ty::Primitive::Float),
-        }),
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: LangType::Primitives(primitive) });
+        }
+
+        HirExprKind::Float(_, suffix) => {
+            let primitive = literal_primitive(*suffix, Primitive::Float);
+            check_literal_range(ctx, expr, &primitive);
+
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: LangType::Primitives(primitive) });
+        }
 
         HirExprKind::Char(_) => ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo {
             ty: LangType::Primitives(middle::ty::Primitive::Char),
@@ -36,64 +466,446 @@ fn infer_expr<'a>(ctx: &GlobalCtx<'a>, expr: &HirExpr) {
 
         HirExprKind::Block(block) => {
             for block_expr in block {
-                infer_expr(ctx, block_expr);
+                infer_expr(ctx, block_expr, fn_ret);
             }
 
-            let last_ty = ctx.module_ty_info.borrow_mut().get_type(&block.last().unwrap().id).cloned();
-            ctx.module_ty_info.borrow_mut().insert_type(expr.id, last_ty.unwrap().clone());
+            let last_ty = match block.last() {
+                Some(last) => ctx.module_ty_info.borrow().get_type(&last.id).unwrap().clone(),
+                None => TypeInfo { ty: LangType::Primitives(Primitive::Unit) }
+            };
+
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, last_ty);
         }
 
-        HirExprKind::Binary { op: _, lhs, rhs } => {
-            infer_expr(ctx, lhs);
-            infer_expr(ctx, rhs);
+        HirExprKind::Binary { op, lhs, rhs } => {
+            infer_expr(ctx, lhs, fn_ret);
+            infer_expr(ctx, rhs, fn_ret);
+
+            // an untyped literal on one side takes the type of the other one
+            if is_untyped_literal(lhs) && !is_untyped_literal(rhs) {
+                let rhs_ty = coerced_type(ctx, rhs);
+                coerce_to(ctx, lhs, &rhs_ty);
+            } else if is_untyped_literal(rhs) && !is_untyped_literal(lhs) {
+                let lhs_ty = coerced_type(ctx, lhs);
+                coerce_to(ctx, rhs, &lhs_ty);
+            }
 
-            let lhs_ty = ctx.module_ty_info.borrow_mut().get_type(&lhs.id).unwrap().clone();
-            //let rhs_ty = ty_table.get_type(rhs.id).unwrap(); - late: todo inference type bounds
+            let lhs_ty = coerced_type(ctx, lhs);
+            let rhs_ty = coerced_type(ctx, rhs);
 
-            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo {
-                ty: lhs_ty.ty.clone(),
-            });
+            if lhs_ty.has_error() || rhs_ty.has_error() || !check_binary_operands(ctx, op, &lhs_ty, &rhs_ty) {
+                poison(ctx, expr);
+                return;
+            }
+
+            // comparisons and logic produce a Bool, everything else the type of its operands
+            let ty = match op {
+                HirBinOps::LT | HirBinOps::GT | HirBinOps::EQ | HirBinOps::AND | HirBinOps::OR => LangType::Primitives(Primitive::Bool),
+                _ => lhs_ty
+            };
+
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty });
         }
 
         HirExprKind::Return(Some(expr_ret)) => {
-            infer_expr(ctx, expr_ret);
-            
-            let ret_expr_ty = ctx.module_ty_info.borrow_mut().get_type(&expr_ret.id).unwrap().clone();
-            ctx.module_ty_info.borrow_mut().insert_type(expr.id, ret_expr_ty.clone());
+            infer_expr(ctx, expr_ret, fn_ret);
+
+            if let Some(fn_ret) = fn_ret {
+                coerce_to(ctx, expr_ret, fn_ret);
+            }
+
+            let ret_expr_ty = coerced_type(ctx, expr_ret);
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: ret_expr_ty });
         }
 
-        HirExprKind::Return(None) => {}
+        HirExprKind::Return(None) => ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo {
+            ty: LangType::Primitives(Primitive::Unit)
+        }),
 
-        HirExprKind::Call { name: _, args} => {
+        HirExprKind::Call { alias: _, name, args} => {
             for arg in args {
-                infer_expr(ctx, arg);
+                infer_expr(ctx, arg, fn_ret);
             }
 
-            let in_scope = ctx.module_symbols.borrow().get_pair(&expr.id).unwrap().clone();
+            let Some(in_scope) = ctx.module_symbols.borrow().get_pair(&expr.id).cloned() else {
+                // the resolver reported the name it could not find
+                poison(ctx, expr);
+                return;
+            };
+
+            if in_scope.kind == SymbolKind::Function {
+                infer_return_type(ctx, in_scope.id);
+            }
             
             let in_scope_ty = ctx.module_ty_info.borrow_mut().get_type(&in_scope.id).unwrap().clone();
-            ctx.module_ty_info.borrow_mut().insert_type(expr.id, in_scope_ty.clone());
+
+            if in_scope_ty.ty == LangType::Error {
+                poison(ctx, expr);
+                return;
+            }
+
+            let LangType::Function { params, ret } = &in_scope_ty.ty else {
+                type_error(ctx, expr, format!("{} has type {} and can not be called", name, in_scope_ty.ty));
+                return;
+            };
+
+            let mut type_params = Vec::new();
+            collect_type_params(&in_scope_ty.ty, &mut type_params);
+
+            if type_params.is_empty() {
+                for (param, arg) in params.iter().zip(args.iter()) {
+                    coerce_to(ctx, arg, param);
+                }
+
+                ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: *ret.clone() });
+                return;
+            }
+
+            let mut type_args = BTreeMap::new();
+
+            for (param, arg) in params.iter().zip(args.iter()) {
+                let arg_ty = ctx.module_ty_info.borrow().get_type(&arg.id).unwrap().ty.clone();
+                infer_type_args(ctx, name, param, &arg_ty, &mut type_args);
+            }
+
+            for type_param in &type_params {
+                if !type_args.contains_key(type_param) {
+                    type_error(ctx, expr, format!("can not infer type parameter {} of {}", type_param, name));
+                    return;
+                }
+            }
+
+            for (param, arg) in params.iter().zip(args.iter()) {
+                coerce_to(ctx, arg, &param.substitute(&type_args));
+            }
+
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: ret.substitute(&type_args) });
+            ctx.module_instances.borrow_mut().add_instance(expr.id, type_args);
         }
 
-        HirExprKind::If { cond, then, _else } => {
-            infer_expr(ctx, cond);
-            infer_expr(ctx, then);
+        HirExprKind::MethodCall { receiver, method, args } => {
+            infer_expr(ctx, receiver, fn_ret);
+
+            for arg in args {
+                infer_expr(ctx, arg, fn_ret);
+            }
+
+            let receiver_ty = ctx.module_ty_info.borrow().get_type(&receiver.id).unwrap().ty.clone();
+
+            if receiver_ty == LangType::Error {
+                poison(ctx, expr);
+                return;
+            }
+
+            if receiver_ty.has_builtin_methods() {
+                let ty = builtin_method_type(ctx, &receiver_ty, method, args);
+                ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty });
+                return;
+            }
+
+            if let LangType::Dyn(trait_name) = &receiver_ty {
+                let method_ty = trait_method_type(ctx, &receiver_ty.short_text(), std::slice::from_ref(trait_name), method);
+
+                // a method that is not found was already reported
+                let LangType::Function { params, ret } = method_ty else {
+                    poison(ctx, expr);
+                    return;
+                };
+
+                for (param, arg) in params.iter().skip(1).zip(args.iter()) {
+                    coerce_to(ctx, arg, param);
+                }
+
+                ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: *ret });
+                return;
+            }
+
+            if let LangType::Param { name, bounds } = &receiver_ty {
+                let method_ty = trait_method_type(ctx, name, bounds, method);
+
+                let LangType::Function { params: _, ret } = method_ty else {
+                    poison(ctx, expr);
+                    return;
+                };
+
+                let self_map = BTreeMap::from([("Self".to_string(), receiver_ty.clone())]);
+
+                ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: ret.substitute(&self_map) });
+                return;
+            }
+
+            let LangType::Struct(ty_name) = &receiver_ty else {
+                type_error(ctx, expr, format!("method {} called on value of type {}", method, receiver_ty));
+                return;
+            };
+
+            let Some(method_info) = ctx.module_methods.borrow().get_method(ty_name, method) else {
+                type_error(ctx, expr, format!("no method {} found for type {}", method, ty_name));
+                return;
+            };
+
+            if !method_info.has_self {
+                type_error(ctx, expr, format!("{}::{} is an associated function and can not be called as a method", ty_name, method));
+                return;
+            }
+
+            ctx.module_symbols.borrow_mut().add_pair(expr.id, SymbolInfo {
+                id: method_info.id,
+                kind: SymbolKind::Function
+            });
+
+            infer_return_type(ctx, method_info.id);
+
+            let method_ty = ctx.module_ty_info.borrow().get_type(&method_info.id).unwrap().ty.clone();
+
+            let LangType::Function { params, ret } = method_ty else {
+                panic!("{}::{} has non function type {:?}", ty_name, method, method_ty);
+            };
 
-            if _else.is_some() {
-                infer_expr(ctx, _else.as_ref().unwrap());
+            for (param, arg) in params.iter().skip(1).zip(args.iter()) {
+                coerce_to(ctx, arg, param);
             }
 
-            // late todo: inference type bounds
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: *ret });
+        }
+
+        HirExprKind::StructLiteral { name, fields } => {
+            for field in fields {
+                infer_expr(ctx, &field.1, fn_ret);
+            }
+
+            let Some(info) = ctx.module_structs.borrow().get(name).cloned() else {
+                type_error(ctx, expr, format!("unknown struct {}", name));
+                return;
+            };
+
+            for (field, value) in fields {
+                if let Some((_, field_ty)) = info.field(field) {
+                    coerce_to(ctx, value, field_ty);
+                }
+            }
 
-            let then_expr_ty = ctx.module_ty_info.borrow_mut().get_type(&then.id).unwrap().clone();
-            ctx.module_ty_info.borrow_mut().insert_type(expr.id, then_expr_ty.clone());
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo {
+                ty: LangType::Struct(name.to_string())
+            });
+        }
+
+        HirExprKind::FieldAccess { expr: inner, field } => {
+            infer_expr(ctx, inner, fn_ret);
+
+            let inner_ty = ctx.module_ty_info.borrow().get_type(&inner.id).unwrap().ty.clone();
+
+            if inner_ty == LangType::Error {
+                poison(ctx, expr);
+                return;
+            }
+
+            let LangType::Struct(ty_name) = &inner_ty else {
+                type_error(ctx, expr, format!("field {} accessed on value of type {}", field, inner_ty));
+                return;
+            };
+
+            let field_ty = ctx.module_structs.borrow().get(ty_name).unwrap()
+                .field(field)
+                .map(|(_, ty)| ty.clone());
+
+            match field_ty {
+                Some(ty) => ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty }),
+                None => type_error(ctx, expr, format!("type {} has no field {}", ty_name, field))
+            }
+        }
+
+        HirExprKind::Str(_) => ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo {
+            ty: LangType::Slice(Box::new(LangType::Primitives(Primitive::Char))),
+        }),
+
+        HirExprKind::Error => poison(ctx, expr),
+
+        HirExprKind::VecNew => ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo {
+            ty: LangType::Vec(Box::new(LangType::UNRESOLVED)),
+        }),
+
+        HirExprKind::Variant { variant, value } => {
+            let value_ty = value.as_ref().map(|value| {
+                infer_expr(ctx, value, fn_ret);
+                ctx.module_ty_info.borrow().get_type(&value.id).unwrap().ty.clone()
+            });
+
+            let ty = match (variant, value_ty) {
+                (HirVariant::Some, Some(ty)) => LangType::Option(Box::new(ty)),
+                (HirVariant::Ok, Some(ty)) => LangType::Result(Box::new(ty), Box::new(LangType::UNRESOLVED)),
+                (HirVariant::Err, Some(err)) => LangType::Result(Box::new(LangType::UNRESOLVED), Box::new(err)),
+                _ => LangType::Option(Box::new(LangType::UNRESOLVED))
+            };
+
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty });
+        }
+
+        HirExprKind::Try(inner) => {
+            infer_expr(ctx, inner, fn_ret);
+
+            let inner_ty = ctx.module_ty_info.borrow().get_type(&inner.id).unwrap().ty.clone();
+
+            if inner_ty == LangType::Error {
+                poison(ctx, expr);
+                return;
+            }
+
+            let (LangType::Option(ty) | LangType::Result(ty, _)) = &inner_ty else {
+                type_error(ctx, expr, format!("the ? operator can not be applied to a value of type {}", inner_ty));
+                return;
+            };
+
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: *ty.clone() });
+        }
+
+        HirExprKind::Defer(inner) => {
+            infer_expr(ctx, inner, fn_ret);
+
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo {
+                ty: LangType::Primitives(Primitive::Unit)
+            });
+        }
+
+        HirExprKind::Array(elems) => {
+            for elem in elems {
+                infer_expr(ctx, elem, fn_ret);
+            }
+
+            let elem_ty = elems.first()
+                .map(|elem| ctx.module_ty_info.borrow().get_type(&elem.id).unwrap().ty.clone())
+                .unwrap_or(LangType::UNRESOLVED);
+
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo {
+                ty: LangType::StaticArray { size: elems.len() as u64, ty: Box::new(elem_ty) }
+            });
+        }
+
+        HirExprKind::Index { expr: inner, index } => {
+            infer_expr(ctx, inner, fn_ret);
+            infer_expr(ctx, index, fn_ret);
+
+            let inner_ty = ctx.module_ty_info.borrow().get_type(&inner.id).unwrap().ty.clone();
+
+            if inner_ty == LangType::Error {
+                poison(ctx, expr);
+                return;
+            }
+
+            let Some(elem_ty) = inner_ty.element_type() else {
+                type_error(ctx, expr, format!("value of type {} can not be indexed", inner_ty));
+                return;
+            };
+
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: elem_ty.clone() });
+        }
+
+        HirExprKind::For { pattern, iterable, body } => {
+            infer_expr(ctx, iterable, fn_ret);
+
+            let iterable_ty = ctx.module_ty_info.borrow().get_type(&iterable.id).unwrap().ty.clone();
+            let elem_ty = match iterable_ty {
+                LangType::Error => LangType::Error,
+                _ => iterable_ty.element_type().cloned().unwrap_or(LangType::UNRESOLVED)
+            };
+
+            type_pattern(ctx, pattern, &elem_ty);
+            infer_expr(ctx, body, fn_ret);
+
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo {
+                ty: LangType::Primitives(Primitive::Unit)
+            });
+        }
+
+        HirExprKind::Tuple(elems) => {
+            for elem in elems {
+                infer_expr(ctx, elem, fn_ret);
+            }
+
+            let elem_tys = elems.iter()
+                .map(|elem| ctx.module_ty_info.borrow().get_type(&elem.id).unwrap().ty.clone())
+                .collect();
+
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: LangType::Tuple(elem_tys) });
+        }
+
+        HirExprKind::TupleIndex { expr: inner, index } => {
+            infer_expr(ctx, inner, fn_ret);
+
+            let inner_ty = ctx.module_ty_info.borrow().get_type(&inner.id).unwrap().ty.clone();
+
+            if inner_ty == LangType::Error {
+                poison(ctx, expr);
+                return;
+            }
+
+            let LangType::Tuple(elems) = &inner_ty else {
+                type_error(ctx, expr, format!("tuple index .{} used on value of type {}", index, inner_ty));
+                return;
+            };
+
+            let Some(elem_ty) = elems.get(*index) else {
+                type_error(ctx, expr, format!("tuple of type {} has no element {}", inner_ty, index));
+                return;
+            };
+
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: elem_ty.clone() });
+        }
+
+        HirExprKind::Closure { args, body } => {
+            let params: Vec<LangType> = args.iter()
+                .map(|arg| translate_hint_to_type(ctx, &arg.2))
+                .collect();
+
+            for (arg, param_ty) in args.iter().zip(params.iter()) {
+                ctx.module_ty_info.borrow_mut().insert_type(arg.1, TypeInfo { ty: param_ty.clone() });
+            }
+
+            infer_expr(ctx, body, None);
+
+            let body_ty = ctx.module_ty_info.borrow().get_type(&body.id).unwrap().ty.clone();
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo {
+                ty: LangType::Function { params, ret: Box::new(body_ty) }
+            });
+        }
+
+        HirExprKind::If { cond, then, _else } => {
+            infer_expr(ctx, cond, fn_ret);
+            infer_expr(ctx, then, fn_ret);
+
+            let Some(_else) = _else else {
+                ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: LangType::Primitives(Primitive::Unit) });
+                return;
+            };
+
+            infer_expr(ctx, _else, fn_ret);
+
+            // a branch that returns never produces a value, so the other one decides the type
+            let ty = match (diverges(then), diverges(_else)) {
+                (true, false) => coerced_type(ctx, _else),
+                (false, true) => coerced_type(ctx, then),
+                _ => {
+                    let then_ty = coerced_type(ctx, then);
+                    coerce_to(ctx, _else, &then_ty);
+
+                    let else_ty = coerced_type(ctx, _else);
+                    coerce_to(ctx, then, &else_ty);
+
+                    // branches that disagree can only be used as a statement, the checker reports any other use
+                    let then_ty = coerced_type(ctx, then);
+                    if then_ty == coerced_type(ctx, _else) { then_ty } else { LangType::Primitives(Primitive::Unit) }
+                }
+            };
+
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty });
         }
 
         HirExprKind::VarDef { name: _, value, ty} => {
-            infer_expr(ctx, value);
+            infer_expr(ctx, value, fn_ret);
 
             if ty.is_some() {
-                let conv_ty = translate_hint_to_type(&ty.clone().unwrap()).clone();
+                let conv_ty = translate_hint_to_type(ctx, &ty.clone().unwrap()).clone();
+                coerce_to(ctx, value, &conv_ty);
                 ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: conv_ty });
                 return;
             }
@@ -102,8 +914,39 @@ fn infer_expr<'a>(ctx: &GlobalCtx<'a>, expr: &HirExpr) {
             ctx.module_ty_info.borrow_mut().insert_type(expr.id, content_type.clone());
         }
 
-        HirExprKind::Id(id) => {
-            let def_id = ctx.module_symbols.borrow().get_pair(&expr.id).unwrap_or_else(|| panic!("Could not find definition for {}", id)).clone();
+        HirExprKind::Destructure { pattern, value, ty } => {
+            infer_expr(ctx, value, fn_ret);
+
+            let value_ty = match ty {
+                Some(ty) => {
+                    let conv_ty = translate_hint_to_type(ctx, ty);
+                    coerce_to(ctx, value, &conv_ty);
+                    conv_ty
+                }
+                None => ctx.module_ty_info.borrow().get_type(&value.id).unwrap().ty.clone()
+            };
+
+            type_pattern(ctx, pattern, &value_ty);
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: value_ty });
+        }
+
+        HirExprKind::Cast { expr: inner, ty } => {
+            infer_expr(ctx, inner, fn_ret);
+
+            let target_ty = translate_hint_to_type(ctx, ty);
+            ctx.module_ty_info.borrow_mut().insert_type(expr.id, TypeInfo { ty: target_ty });
+        }
+
+        HirExprKind::Id(_) | HirExprKind::Path { .. } => {
+            let Some(def_id) = ctx.module_symbols.borrow().get_pair(&expr.id).cloned() else {
+                // the resolver reported the name it could not find
+                poison(ctx, expr);
+                return;
+            };
+
+            if def_id.kind == SymbolKind::Function {
+                infer_return_type(ctx, def_id.id);
+            }
 
             let old_ty = ctx.module_ty_info.borrow_mut().get_type(&def_id.id).unwrap().clone();
             ctx.module_ty_info.borrow_mut().insert_type(expr.id, old_ty);
@@ -111,23 +954,239 @@ fn infer_expr<'a>(ctx: &GlobalCtx<'a>, expr: &HirExpr) {
     }
 }
 
+// when inference is enabled the return type of a private function may be left out, public ones keep it
+// written down so that the interface of the module only changes when its author means it to
+fn infers_return_type(ctx: &GlobalCtx, item: &HirModuleItem) -> bool {
+    ctx.infer_return_types && matches!(item, HirModuleItem::Func { ret_ty: None, visibility: HirVisibility::Private, .. })
+}
+
+fn type_function_signature<'a>(ctx: &GlobalCtx<'a>, item: &HirModuleItem<'a>) {
+    let HirModuleItem::Func { id, name: _, args, patterns, ret_ty, .. } = item else {
+        return;
+    };
+
+    let conv_ty = if ret_ty.is_some() {
+        translate_hint_to_type(ctx, &ret_ty.clone().unwrap()).clone() 
+    } else if infers_return_type(ctx, item) {
+        ctx.module_return_inference.borrow_mut().add_pending(*id);
+        LangType::UNRESOLVED
+    } else {
+        LangType::Primitives(middle::ty::Primitive::Unit)
+    };
+
+    let mut params = Vec::new();
+
+    for arg in args {
+        let arg_ty = translate_hint_to_type(ctx, &arg.2.clone()).clone();
+        params.push(arg_ty.clone());
+        ctx.module_ty_info.borrow_mut().insert_type(arg.1, TypeInfo { ty: arg_ty });
+    } 
+
+    for (index, pattern) in patterns {
+        type_pattern(ctx, pattern, &params[*index]);
+    }
+
+    ctx.module_ty_info.borrow_mut().insert_type(*id, TypeInfo { 
+        ty: LangType::Function { params, ret: Box::new(conv_ty) } 
+    });
+}
+
+fn type_function_body<'a>(ctx: &GlobalCtx<'a>, item: &HirModuleItem<'a>) {
+    let HirModuleItem::Func { id, body, .. } = item else {
+        return;
+    };
+
+    let fn_ty = ctx.module_ty_info.borrow().get_type(id).unwrap().ty.clone();
+    let LangType::Function { ret, .. } = &fn_ty else {
+        panic!("function has non function type {:?}", fn_ty);
+    };
+
+    // a return type that is still being inferred can not guide the body yet
+    let ret = Some(ret.as_ref()).filter(|ret| ret.is_resolved());
+
+    match &body.kind {
+        HirExprKind::Block(exprs) => {
+            for expr in exprs {
+                infer_expr(ctx, expr, ret);
+            }
+
+            let last_ty =  ctx.module_ty_info.borrow_mut().get_type(&exprs.last().unwrap().id).cloned();
+            ctx.module_ty_info.borrow_mut().insert_type(body.id, last_ty.unwrap().clone());
+
+            if let Some(ret) = ret {
+                coerce_to(ctx, body, ret);
+            }
+        },
+
+        _ => panic!("Unsupported body type for function")
+    }; 
+}
+
+fn find_function<'c, 'a>(ctx: &'c GlobalCtx<'a>, id: HirId) -> Option<&'c HirModuleItem<'a>> {
+    ctx.module_files.iter()
+        .flat_map(|file| file.items.iter())
+        .flat_map(|item| match item {
+            HirModuleItem::Impl { methods, .. } => methods.iter().collect(),
+            _ => vec![item]
+        })
+        .find(|item| matches!(item, HirModuleItem::Func { id: func_id, .. } if *func_id == id))
+}
+
+// values of `ret` statements, which only appear where a statement can stand
+fn collect_returns<'c, 'a>(expr: &'c HirExpr<'a>, returns: &mut Vec<&'c HirExpr<'a>>) {
+    match &expr.kind {
+        HirExprKind::Return(_) => returns.push(expr),
+        HirExprKind::Block(exprs) => {
+            for expr in exprs {
+                collect_returns(expr, returns);
+            }
+        }
+        HirExprKind::If { cond: _, then, _else } => {
+            collect_returns(then, returns);
+
+            if let Some(_else) = _else {
+                collect_returns(_else, returns);
+            }
+        }
+        HirExprKind::For { body, .. } => collect_returns(body, returns),
+        _ => ()
+    }
+}
+
+// types the body of a function without a written return type the first time the function is needed,
+// the return type being the first one its `ret` statements or its last expression produce
+fn infer_return_type<'a>(ctx: &GlobalCtx<'a>, id: HirId) {
+    if ctx.module_return_inference.borrow().is_in_progress(&id) {
+        let Some(HirModuleItem::Func { name, .. }) = find_function(ctx, id) else {
+            unreachable!();
+        };
+
+        ctx.error(format!("can not infer the return type of {} as it is used recursively, add an explicit return type", name));
+
+        // the uses of the function are poisoned until its body gives it a type
+        let fn_ty = ctx.module_ty_info.borrow().get_type(&id).unwrap().ty.clone();
+        let LangType::Function { params, .. } = fn_ty else {
+            unreachable!();
+        };
+
+        ctx.module_ty_info.borrow_mut().insert_type(id, TypeInfo { ty: LangType::Function { params, ret: Box::new(LangType::Error) } });
+        return;
+    }
+
+    if !ctx.module_return_inference.borrow_mut().start(id) {
+        return;
+    }
+
+    let item = find_function(ctx, id).unwrap();
+    type_function_body(ctx, item);
+
+    let HirModuleItem::Func { body, .. } = item else {
+        unreachable!();
+    };
+
+    let mut returns = Vec::new();
+    collect_returns(body, &mut returns);
+
+    let mut candidates: Vec<LangType> = returns.iter()
+        .map(|ret| match &ret.kind {
+            HirExprKind::Return(Some(value)) => coerced_type(ctx, value),
+            _ => LangType::Primitives(Primitive::Unit)
+        })
+        .collect();
+
+    // a body that always returns has no value of its own, a divergent body holds at least one `ret`
+    if !diverges(body) {
+        candidates.push(coerced_type(ctx, body));
+    }
+
+    let ret = candidates.iter().find(|ty| ty.is_resolved()).unwrap_or(&candidates[0]).clone();
+
+    for expr in returns {
+        if let HirExprKind::Return(Some(value)) = &expr.kind {
+            coerce_to(ctx, value, &ret);
+        }
+    }
+
+    coerce_to(ctx, body, &ret);
+
+    let fn_ty = ctx.module_ty_info.borrow().get_type(&id).unwrap().ty.clone();
+    let LangType::Function { params, .. } = fn_ty else {
+        unreachable!();
+    };
+
+    ctx.module_ty_info.borrow_mut().insert_type(id, TypeInfo { ty: LangType::Function { params, ret: Box::new(ret) } });
+    ctx.module_return_inference.borrow_mut().finish(id);
+}
+
 pub fn type_hir_module(ctx: &mut GlobalCtx) {
+    for (id, function) in ctx.module_imports.borrow().iter() {
+        ctx.module_ty_info.borrow_mut().insert_type(*id, TypeInfo { ty: function.ty.clone() });
+    }
+
+    for file in &ctx.module_files {
+        for elem in file.items.iter() {
+            if let HirModuleItem::TypeAlias { name, .. } = elem {
+                alias_type(ctx, name);
+            }
+        }
+    }
+
+    for file in &ctx.module_files {
+        for elem in file.items.iter() {
+            if let HirModuleItem::Struct { id, name, fields, .. } = elem {
+                let fields = fields.iter()
+                    .map(|field| (field.0, translate_hint_to_type(ctx, &field.1)))
+                    .collect();
+
+                // a duplicate definition does not replace the fields of the first one
+                if ctx.module_structs.borrow().get(name).is_some_and(|info| info.id == *id) {
+                    ctx.module_structs.borrow_mut().set_fields(name, fields);
+                }
+            }
+        }
+    }
+
+    for file in &ctx.module_files {
+        for elem in file.items.iter() {
+            if let HirModuleItem::Trait { id, name, methods, .. } = elem {
+                let methods = methods.iter().map(|method| {
+                    let params = method.args.iter()
+                        .map(|arg| translate_hint_to_type(ctx, &arg.1))
+                        .collect();
+
+                    let ret = method.ret_ty.as_ref()
+                        .map(|ret| translate_hint_to_type(ctx, ret))
+                        .unwrap_or(LangType::Primitives(Primitive::Unit));
+
+                    let ty = LangType::Function { params, ret: Box::new(ret) };
+                    ctx.module_ty_info.borrow_mut().insert_type(method.id, TypeInfo { ty: ty.clone() });
+
+                    TraitMethodInfo { name: method.name, ty }
+                }).collect();
+
+                if ctx.module_traits.borrow().get(name).is_some_and(|info| info.id == *id) {
+                    ctx.module_traits.borrow_mut().set_methods(name, methods);
+                }
+            }
+        }
+    }
+
     for file in &ctx.module_files {
         for elem in file.items.iter() {
             match elem {
-                HirModuleItem::Func { id, name: _, args, ret_ty, .. } => {
-                    let conv_ty = if ret_ty.is_some() {
-                        translate_hint_to_type(&ret_ty.clone().unwrap()).clone() 
-                    } else {
-                        LangType::Primitives(middle::ty::Primitive::Unit)
-                    };
+                HirModuleItem::Func { .. } => type_function_signature(ctx, elem),
 
+                HirModuleItem::Const { id, ty, .. } => {
+                    let conv_ty = translate_hint_to_type(ctx, ty);
                     ctx.module_ty_info.borrow_mut().insert_type(id.clone(), TypeInfo { ty: conv_ty });
+                }
+
+                HirModuleItem::Struct { .. } | HirModuleItem::Trait { .. } | HirModuleItem::TypeAlias { .. } => (),
 
-                    for arg in args {
-                        let arg_ty = translate_hint_to_type(&arg.2.clone()).clone();
-                        ctx.module_ty_info.borrow_mut().insert_type(arg.1.clone(), TypeInfo { ty: arg_ty });
-                    } 
+                HirModuleItem::Impl { methods, .. } => {
+                    for method in methods {
+                        type_function_signature(ctx, method);
+                    }
                 }
             }
         }
@@ -136,23 +1195,32 @@ pub fn type_hir_module(ctx: &mut GlobalCtx) {
     for file in &ctx.module_files {
         for elem in file.items.iter() {
             match elem {
-                HirModuleItem::Func { id: _, name: _, args: _, body, .. } => {
-                    match &body.kind {
-                        HirExprKind::Block(exprs) => {
-                            for expr in exprs {
-                                infer_expr(ctx, expr);
-                            }
-
-                            let last_ty =  ctx.module_ty_info.borrow_mut().get_type(&exprs.last().unwrap().id).cloned();
-                            ctx.module_ty_info.borrow_mut().insert_type(body.id, last_ty.unwrap().clone());
-                        },
-
-                        _ => panic!("Unsupported body type for function")
-                    }; 
+                HirModuleItem::Func { id, .. } if infers_return_type(ctx, elem) => infer_return_type(ctx, *id),
+                HirModuleItem::Func { .. } => type_function_body(ctx, elem),
+
+                HirModuleItem::Const { id, value, .. } => {
+                    infer_expr(ctx, value, None);
+
+                    let const_ty = ctx.module_ty_info.borrow().get_type(id).unwrap().ty.clone();
+                    coerce_to(ctx, value, &const_ty);
+                }
+
+                HirModuleItem::Struct { .. } | HirModuleItem::Trait { .. } | HirModuleItem::TypeAlias { .. } => (),
+
+                HirModuleItem::Impl { methods, .. } => {
+                    for method in methods {
+                        match method {
+                            HirModuleItem::Func { id, .. } if infers_return_type(ctx, method) => infer_return_type(ctx, *id),
+                            _ => type_function_body(ctx, method)
+                        }
+                    }
                 }
             }
         }
     }
 
     //ctx.module_ty_info.borrow_mut().dump();
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests;
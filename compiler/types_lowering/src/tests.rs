@@ -0,0 +1,151 @@
+use ast_lowering::translate_to_hir;
+use hir::{HirExprKind, HirModuleItem};
+use hir_resolver::resolve_module;
+use middle::{BuildType, GlobalCtx};
+use parser::parse_file;
+
+use crate::type_hir_module;
+
+// the errors reported while typing the source and the type of the last expression of its function `f`
+fn type_source(source: &str) -> (Vec<String>, String) {
+    type_with_inference(source, false)
+}
+
+fn type_with_inference(source: &str, infer_return_types: bool) -> (Vec<String>, String) {
+    let parsed = parse_file("test", source);
+
+    let mut ctx = GlobalCtx::new("test".to_string(), "x86_64".to_string(), BuildType::Executable);
+    ctx.infer_return_types = infer_return_types;
+    ctx.module_files.push(translate_to_hir(&ctx, &parsed));
+
+    resolve_module(&mut ctx);
+    type_hir_module(&mut ctx);
+
+    let body = ctx.module_files.iter()
+        .flat_map(|file| file.items.iter())
+        .find_map(|item| match item {
+            HirModuleItem::Func { name: "f", body, .. } => Some(body),
+            _ => None
+        })
+        .unwrap();
+
+    let HirExprKind::Block(exprs) = &body.kind else {
+        panic!("function body is not a block");
+    };
+
+    let last_ty = ctx.module_ty_info.borrow().get_type(&exprs.last().unwrap().id).unwrap().ty.to_string();
+
+    (ctx.module_errors.take(), last_ty)
+}
+
+#[test]
+fn types_arithmetic_as_its_operands() {
+    assert_eq!(type_source("fn f(a: I32, b: I32) -> I32 { a * b; }"), (vec![], "I32".to_string()));
+    assert_eq!(type_source("fn f(a: Float, b: Float) -> Float { a - b; }"), (vec![], "Float".to_string()));
+}
+
+#[test]
+fn types_comparisons_as_bool() {
+    assert_eq!(type_source("fn f(a: Int, b: Int) -> Bool { a > b; }"), (vec![], "Bool".to_string()));
+    assert_eq!(type_source("fn f(a: Char, b: Char) -> Bool { a == b; }"), (vec![], "Bool".to_string()));
+}
+
+#[test]
+fn types_logical_operators_as_bool() {
+    assert_eq!(type_source("fn f(a: Bool, b: Bool) -> Bool { a && b; }"), (vec![], "Bool".to_string()));
+    assert_eq!(type_source("fn f(a: Int, b: Int) -> Bool { a < b || a > b; }"), (vec![], "Bool".to_string()));
+}
+
+#[test]
+fn rejects_logical_operators_on_integers() {
+    let (errors, ty) = type_source("fn f(a: Int, b: Int) -> Bool { a && b; }");
+
+    assert_eq!(errors, ["operator && can not be applied to values of type Int"]);
+    assert_eq!(ty, "{error}");
+}
+
+#[test]
+fn rejects_arithmetic_on_mismatched_operands() {
+    let (errors, ty) = type_source("fn f(a: I32, b: I64) -> I32 { a + b; }");
+
+    assert_eq!(errors, ["operator + has operands of different types: I32 and I64"]);
+    assert_eq!(ty, "{error}");
+}
+
+#[test]
+fn rejects_arithmetic_on_bools() {
+    let (errors, ty) = type_source("fn f(a: Bool, b: Bool) -> Bool { a + b; }");
+
+    assert_eq!(errors, ["operator + can not be applied to values of type Bool"]);
+    assert_eq!(ty, "{error}");
+}
+
+#[test]
+fn types_untyped_literals_from_context() {
+    assert_eq!(type_source("fn f(a: U8) -> U8 { a + 1; }"), (vec![], "U8".to_string()));
+    assert_eq!(type_source("fn f() -> F32 { val x: F32 = 1.5; x; }"), (vec![], "F32".to_string()));
+    assert_eq!(type_source("fn f() -> I16 { 2 * 3 + 4; }"), (vec![], "I16".to_string()));
+}
+
+#[test]
+fn defaults_untyped_literals_without_context() {
+    assert_eq!(type_source("fn f() { val x = 1; x; }"), (vec![], "Int".to_string()));
+    assert_eq!(type_source("fn f() { val x = 1.5; x; }"), (vec![], "Float".to_string()));
+}
+
+#[test]
+fn rejects_literals_out_of_range() {
+    assert_eq!(type_source("fn f() -> U8 { val x: U8 = 300; x; }").0, ["300 does not fit in U8"]);
+    assert_eq!(type_source("fn f(a: I8) -> I8 { a + 128; }").0, ["128 does not fit in I8"]);
+    assert_eq!(type_source("fn f() -> U8 { val x: U8 = -1; x; }").0, ["-1 does not fit in U8"]);
+    assert_eq!(type_source("fn f() -> U8 { val x: U8 = 255; x; }").0, Vec::<String>::new());
+}
+
+#[test]
+fn checks_literals_beyond_the_range_of_int() {
+    assert_eq!(type_source("fn f() -> I8 { val x: I8 = -128; x; }").0, Vec::<String>::new());
+    assert_eq!(type_source("fn f() -> I8 { val x: I8 = -129; x; }").0, ["-129 does not fit in I8"]);
+    assert_eq!(type_source("fn f() -> U64 { 18446744073709551615u64; }").0, Vec::<String>::new());
+    assert_eq!(type_source("fn f() -> Int { 9223372036854775808; }").0, ["9223372036854775808 does not fit in Int"]);
+    assert_eq!(type_source("fn f() -> U128 { 0xffffffffffffffffffffffffffffffffu128; }").0, Vec::<String>::new());
+    assert_eq!(type_source("fn f() -> I128 { -170141183460469231731687303715884105728i128; }").0, Vec::<String>::new());
+}
+
+#[test]
+fn types_const_values_from_their_declared_type() {
+    assert_eq!(type_source("const X: U8 = 1; fn f() -> U8 { X; }"), (vec![], "U8".to_string()));
+    assert_eq!(type_source("const X: U8 = 300; fn f() -> U8 { X; }").0, ["300 does not fit in U8"]);
+}
+
+#[test]
+fn types_literal_arguments_of_generic_calls_from_their_params() {
+    let source = "fn pick<T>(value: T, byte: U8) -> T { ret value; } fn f() -> Bool { pick(true, 300); }";
+
+    assert_eq!(type_source(source), (vec!["300 does not fit in U8".to_string()], "Bool".to_string()));
+}
+
+#[test]
+fn types_literals_nested_in_arrays_tuples_and_variants() {
+    assert_eq!(type_source("fn f() -> (U8, Option<I16>) { (1, Some(2)); }"), (vec![], "(U8, Option<I16>)".to_string()));
+    assert_eq!(type_source("fn f() -> [U8; 2] { [1, 2]; }"), (vec![], "[U8; 2]".to_string()));
+    assert_eq!(type_source("fn f() -> Option<U8> { Some(256); }").0, ["256 does not fit in U8"]);
+    assert_eq!(type_source("fn f() -> Result<U8, I8> { Err(200); }").0, ["200 does not fit in I8"]);
+}
+
+#[test]
+fn keeps_unit_for_missing_return_types_unless_inference_is_enabled() {
+    let source = "fn work() { 1; } fn helper() { work(); 0; } fn f() { helper(); }";
+
+    assert_eq!(type_source(source), (vec![], "()".to_string()));
+    assert_eq!(type_with_inference(source, true), (vec![], "Int".to_string()));
+}
+
+#[test]
+fn reports_recursive_inferred_functions() {
+    let source = "fn fact(n: Int) { if n < 2 { ret 1; } n * fact(n - 1); } fn f() { fact(3); }";
+
+    assert_eq!(
+        type_with_inference(source, true).0,
+        ["can not infer the return type of fact as it is used recursively, add an explicit return type"]
+    );
+}